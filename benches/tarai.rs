@@ -3,7 +3,7 @@
 use criterion::{criterion_group, criterion_main, Criterion, ParameterizedBenchmark};
 use std::fmt;
 
-use mini_lang::{execute, EagerEval, Evaluator, LazyEval, Printer};
+use mini_lang::{execute, EagerEval, Evaluator, LazyEval, Printer, Value};
 
 struct NopPrinter;
 
@@ -20,7 +20,7 @@ impl std::error::Error for EmptyError {}
 
 impl Printer for NopPrinter {
     type Err = EmptyError;
-    fn print(&mut self, _v: i32) -> Result<(), EmptyError> {
+    fn print(&mut self, _v: &Value) -> Result<(), EmptyError> {
         Ok(())
     }
 }
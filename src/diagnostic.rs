@@ -0,0 +1,260 @@
+use crate::{MiniError, Span, Warning};
+use std::fmt::Write;
+
+/// The secondary label attached to a diagnostic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    /// The location to be labeled.
+    pub span: Span,
+    /// The message shown beside the underline.
+    pub message: String,
+}
+
+impl Label {
+    /// Create a new label.
+    pub fn new<S: Into<String>>(span: Span, message: S) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// Whether the diagnostic stops the execution.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// The human readable report of an error, which can be rendered with the source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The main message.
+    pub message: String,
+    /// The primary location of the error, if exists.
+    pub span: Option<Span>,
+    /// The tokens expected at the location, in human words.
+    pub expected: Vec<String>,
+    /// The secondary labels.
+    pub labels: Vec<Label>,
+}
+
+impl From<&MiniError> for Diagnostic {
+    fn from(err: &MiniError) -> Self {
+        match err {
+            MiniError::Parse(e) => {
+                let start = e.location.offset;
+                let mut expected = e.expected.tokens().filter_map(humanize).collect::<Vec<_>>();
+                expected.sort();
+                expected.dedup();
+                Self {
+                    severity: Severity::Error,
+                    message: String::from("Parse Error"),
+                    span: Some(Span {
+                        start,
+                        end: start + 1,
+                        line: e.location.line,
+                        column: e.location.column,
+                    }),
+                    expected,
+                    labels: Vec::new(),
+                }
+            }
+            MiniError::Execution { kind, span, labels } => Self {
+                severity: Severity::Error,
+                message: format!("Execution Error: {}", kind),
+                span: *span,
+                expected: Vec::new(),
+                labels: labels.clone(),
+            },
+            MiniError::Type { kind, span, labels } => Self {
+                severity: Severity::Error,
+                message: format!("Type Error: {}", kind),
+                span: *span,
+                expected: Vec::new(),
+                labels: labels.clone(),
+            },
+            // Only the first error is reported. (Use `render` to report all of them.)
+            MiniError::Multiple(errors) => Self::from(&errors[0]),
+            MiniError::Any(e) => match e.downcast_ref::<MiniError>() {
+                Some(e) => Self::from(e),
+                None => Self {
+                    severity: Severity::Error,
+                    message: e.to_string(),
+                    span: None,
+                    expected: Vec::new(),
+                    labels: Vec::new(),
+                },
+            },
+        }
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: warning.kind.to_string(),
+            span: Some(warning.span),
+            expected: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+/// Translate the token from `peg::error::ExpectedSet` into human words.
+fn humanize(token: &str) -> Option<String> {
+    Some(match token {
+        "EOF" => String::from("end of input"),
+        t if t.starts_with("['a'") => String::from("an identifier"),
+        t if t.starts_with("['0'") => String::from("a number"),
+        // Whitespaces and comments are expected almost everywhere.
+        "\" \"" | "\"\\t\"" | "\"\\n\"" | "\"\\r\"" | "\"\\r\\n\"" | "\"\\\\\"" | "\"//\""
+        | "\"/*\"" => return None,
+        t if t.starts_with('"') && t.ends_with('"') && t.len() > 1 => {
+            format!("`{}`", unescape(&t[1..t.len() - 1]))
+        }
+        // Named by `expected!` in the grammar.
+        t if t.starts_with("a ") || t.starts_with("an ") => String::from(t),
+        _ => return None,
+    })
+}
+
+/// Strip the escapes of `Debug`, which peg uses to show the literal tokens.
+fn unescape(token: &str) -> String {
+    let mut res = String::new();
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        res.push(match c {
+            '\\' => chars.next().unwrap_or(c),
+            c => c,
+        });
+    }
+    res
+}
+
+/// Join the words as "a", "a or b", or "a, b, or c".
+fn one_of(words: &[String]) -> String {
+    match words {
+        [] => String::new(),
+        [w] => w.clone(),
+        [a, b] => format!("{} or {}", a, b),
+        [init @ .., last] => format!("{}, or {}", init.join(", "), last),
+    }
+}
+
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(&self, code: &str, s: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", code, s)
+        } else {
+            String::from(s)
+        }
+    }
+}
+
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const BLUE: &str = "1;34";
+const BOLD: &str = "1";
+
+impl Diagnostic {
+    /// Render the diagnostic with the snippets of `src`, using ANSI colors if `color` is `true`.
+    pub fn render(&self, src: &str, color: bool) -> String {
+        let style = Style { color };
+        let (title, primary_color) = match self.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}{}",
+            style.paint(primary_color, title),
+            style.paint(BOLD, &format!(": {}", self.message))
+        );
+
+        let mut spans = Vec::new();
+        if let Some(span) = self.span {
+            let message = if self.expected.is_empty() {
+                String::new()
+            } else {
+                format!("expected {}", one_of(&self.expected))
+            };
+            spans.push((span, message, true));
+        }
+        spans.extend(
+            self.labels
+                .iter()
+                .map(|l| (l.span, l.message.clone(), false)),
+        );
+        if spans.is_empty() {
+            return out;
+        }
+        spans.sort_by_key(|(span, _, _)| (span.line, span.column));
+
+        let lines = src.lines().collect::<Vec<_>>();
+        let width = spans
+            .iter()
+            .map(|(span, _, _)| span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = style.paint(BLUE, &format!("{} |", " ".repeat(width)));
+        let (first, _, _) = spans.iter().find(|(_, _, p)| *p).unwrap_or(&spans[0]);
+        let _ = writeln!(
+            out,
+            "{}{} {}",
+            " ".repeat(width),
+            style.paint(BLUE, "-->"),
+            first
+        );
+        let _ = writeln!(out, "{}", gutter);
+        for (span, message, primary) in spans {
+            let line = lines.get(span.line - 1).copied().unwrap_or("");
+            let rest = line.chars().count().saturating_sub(span.column - 1);
+            let len = src
+                .get(span.start..span.end)
+                .map(|s| s.chars().take_while(|c| *c != '\n').count())
+                .unwrap_or(1)
+                .clamp(1, rest.max(1));
+            let (mark, code) = if primary {
+                ("^", primary_color)
+            } else {
+                ("-", BLUE)
+            };
+            let snippet = format!(
+                "{} {}",
+                style.paint(BLUE, &format!("{:>w$} |", span.line, w = width)),
+                line
+            );
+            let _ = writeln!(out, "{}", snippet.trim_end());
+            let underline = format!("{} {}", mark.repeat(len), message);
+            let _ = writeln!(
+                out,
+                "{} {}{}",
+                gutter,
+                " ".repeat(span.column - 1),
+                style.paint(code, underline.trim_end())
+            );
+        }
+        out
+    }
+}
+
+/// Render `err` with the snippets of `src`, using ANSI colors if `color` is `true`.
+pub fn render(err: &MiniError, src: &str, color: bool) -> String {
+    match err {
+        MiniError::Multiple(errors) => errors
+            .iter()
+            .map(|e| render(e, src, color))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => Diagnostic::from(err).render(src, color),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{Label, Operator, Span, UnaryOperator};
 use peg::{error::ParseError, str::LineCol};
 
 /// The result type for this crate.
@@ -8,21 +9,204 @@ pub type MiniResult<T> = Result<T, MiniError>;
 pub enum MiniError {
     #[error("Parse Error: {0}")]
     Parse(ParseError<LineCol>),
-    #[error("Execution Error: {0}")]
-    Execution(String),
+    #[error("Execution Error{}: {kind}", .span.map(|s| format!(" at {}", s)).unwrap_or_default())]
+    Execution {
+        kind: ErrorKind,
+        span: Option<Span>,
+        labels: Vec<Label>,
+    },
+    /// Found by `types::infer` before the evaluation. (`kind` is boxed not to grow the error.)
+    #[error("Type Error{}: {kind}", .span.map(|s| format!(" at {}", s)).unwrap_or_default())]
+    Type {
+        kind: Box<ErrorKind>,
+        span: Option<Span>,
+        labels: Vec<Label>,
+    },
+    #[error("{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+    Multiple(Vec<MiniError>),
     #[error("{0}")]
-    Any(Box<dyn std::error::Error>),
+    Any(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// The kinds of errors occured while compiling or evaluating.
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    #[error("Using undefined variable.")]
+    UndefinedVariable { name: String },
+    #[error("Using undefined function.")]
+    UndefinedFunction { name: String },
+    #[error("Overflowed {}", operation_name(*.op))]
+    Overflow { op: Operator },
+    #[error("{}", division_by_zero(*.op))]
+    DivisionByZero { op: Operator },
+    #[error("Overflowed {}", unary_name(*.op))]
+    UnaryOverflow { op: UnaryOperator },
+    #[error("Raised an integer to a negative power.")]
+    NegativeExponent,
+    #[error("The integer literal is out of range.")]
+    LiteralOutOfRange { literal: String },
+    #[error("The number can't be represented as an integer.")]
+    IntConversion,
+    #[error("Took the head or the tail of the empty list.")]
+    EmptyList,
+    #[error("The value has no such field.")]
+    NoField { name: String },
+    #[error("Defined the field twice.")]
+    DuplicateField { name: String },
+    #[error("Using undefined constructor.")]
+    UndefinedConstructor { name: String },
+    #[error("The number of fields doesn't match the constructor.")]
+    FieldCount { name: String },
+    #[error("The patterns don't cover `{missing}`.")]
+    NonExhaustive { missing: String },
+    #[error("The clauses have different numbers of arguments.")]
+    ClauseArity { name: String },
+    #[error("Never matches, since the earlier patterns cover it.")]
+    Unreachable,
+    #[error("Defined the name twice.")]
+    Redefinition { name: String },
+    #[error("The variable is bound more than once after it's used.")]
+    AmbiguousVariable { name: String },
+    #[error("Cyclic dependency between bindings.")]
+    CyclicBinding,
+    #[error("The value is borrowed.")]
+    BorrowConflict,
+    /// Found at runtime, or by `types::infer`, where the types are quoted like `` `Int` `` or
+    /// described like `a number`.
+    #[error("Expected {expected}, found {found}.")]
+    TypeMismatch { expected: String, found: String },
+    #[error("Using undefined type.")]
+    UndefinedType { name: String },
+    #[error("The number of type arguments doesn't match the type.")]
+    TypeArgs { name: String },
+    #[error("The type `{var}` can't be `{ty}`, which contains itself.")]
+    InfiniteType { var: String, ty: String },
+}
+
+/// The problem found by the compiler, which doesn't prevent the evaluation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+fn operation_name(op: Operator) -> &'static str {
+    match op {
+        Operator::Add => "addition",
+        Operator::Sub => "subtraction",
+        Operator::Mul => "multiplication",
+        Operator::Div | Operator::TruncDiv | Operator::EuclidDiv => "division, or division by zero",
+        Operator::Rem | Operator::TruncRem | Operator::EuclidRem => {
+            "remainder, or division by zero"
+        }
+        Operator::Pow => "power",
+        Operator::Shl => "left shift",
+        Operator::Shr => "right shift",
+        _ => "operation",
+    }
+}
+
+fn division_by_zero(op: Operator) -> String {
+    match op {
+        Operator::Pow => String::from("Division by zero in power"),
+        op => format!("Overflowed {}", operation_name(op)),
+    }
+}
+
+fn unary_name(op: UnaryOperator) -> &'static str {
+    match op {
+        UnaryOperator::Neg => "negation",
+        _ => "operation",
+    }
 }
 
 impl MiniError {
     /// Put any kinds of error into `MiniError`.
-    pub fn from_error<E: std::error::Error + 'static>(error: E) -> Self {
+    pub fn from_error<E: std::error::Error + Send + Sync + 'static>(error: E) -> Self {
         Self::Any(Box::new(error))
     }
+
+    /// Locate the error at `span`, unless it already has the location.
+    pub fn at(self, span: Span) -> Self {
+        match self {
+            Self::Execution {
+                kind,
+                span: None,
+                labels,
+            } => Self::Execution {
+                kind,
+                span: Some(span),
+                labels,
+            },
+            Self::Type {
+                kind,
+                span: None,
+                labels,
+            } => Self::Type {
+                kind,
+                span: Some(span),
+                labels,
+            },
+            e => e,
+        }
+    }
+
+    /// The type error of `kind` at `span`.
+    pub fn type_error(kind: ErrorKind, span: Span) -> Self {
+        Self::Type {
+            kind: Box::new(kind),
+            span: Some(span),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Bundle the errors into one. (`errors` must not be empty.)
+    pub fn from_errors(mut errors: Vec<MiniError>) -> Self {
+        if errors.len() == 1 {
+            errors.remove(0)
+        } else {
+            Self::Multiple(errors)
+        }
+    }
+
+    /// Attach the secondary label to the error.
+    pub fn with_label<S: Into<String>>(mut self, span: Span, message: S) -> Self {
+        if let Self::Execution { labels, .. } | Self::Type { labels, .. } = &mut self {
+            labels.push(Label::new(span, message));
+        }
+        self
+    }
+
+    /// The kind of the error, if it occured while compiling or evaluating.
+    /// (For `Multiple`, the kind of the first one.)
+    pub fn kind(&self) -> Option<&ErrorKind> {
+        match self {
+            Self::Execution { kind, .. } => Some(kind),
+            Self::Type { kind, .. } => Some(kind),
+            Self::Multiple(errors) => errors.first().and_then(Self::kind),
+            Self::Any(e) => e.downcast_ref::<Self>().and_then(Self::kind),
+            _ => None,
+        }
+    }
+
+    /// The location of the error, if exists.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Execution { span, .. } | Self::Type { span, .. } => *span,
+            Self::Multiple(errors) => errors.first().and_then(Self::span),
+            Self::Any(e) => e.downcast_ref::<Self>().and_then(Self::span),
+            _ => None,
+        }
+    }
 }
 
-impl<T: Into<String>> From<T> for MiniError {
-    fn from(s: T) -> Self {
-        Self::Execution(s.into())
+impl From<ErrorKind> for MiniError {
+    fn from(kind: ErrorKind) -> Self {
+        Self::Execution {
+            kind,
+            span: None,
+            labels: Vec::new(),
+        }
     }
 }
@@ -0,0 +1,107 @@
+use super::operation::ratio;
+use super::{force_deep, Thunk};
+use crate::ir::{Builtin, Operator};
+use crate::value::Cons;
+use crate::{ErrorKind, Int, IntType, MiniResult, Ratio, Value};
+use std::rc::Rc;
+
+/// Call the builtin function, where integers are made as `int`, and the arguments are
+/// evaluated by `force` when needed.
+pub fn builtin<F>(b: Builtin, args: Vec<Thunk>, int: IntType, force: &F) -> MiniResult<Value>
+where
+    F: Fn(&Thunk) -> MiniResult<Value>,
+{
+    // Lists are handled here, and the others separately, to keep the frame small for the
+    // recursions through `head` and `tail`.
+    match b {
+        Builtin::Cons => {
+            let mut args = args.into_iter();
+            let (head, tail) = (args.next().unwrap(), args.next().unwrap());
+            Ok(Value::Cons(Rc::new(Cons { head, tail })))
+        }
+        Builtin::Nil => Ok(Value::Nil),
+        Builtin::Head => force(&cons(force(&args[0])?)?.head),
+        Builtin::Tail => force(&cons(force(&args[0])?)?.tail),
+        Builtin::IsNil => match force(&args[0])? {
+            Value::Nil => Ok(Value::Bool(true)),
+            Value::Cons(_) => Ok(Value::Bool(false)),
+            v => Err(v.mismatch("a list").into()),
+        },
+        b => convert(b, &args, int, force),
+    }
+}
+
+/// Call the builtin function converting numbers or strings.
+#[inline(never)]
+fn convert<F>(b: Builtin, args: &[Thunk], int: IntType, force: &F) -> MiniResult<Value>
+where
+    F: Fn(&Thunk) -> MiniResult<Value>,
+{
+    let mut args = args.iter();
+    let mut arg = || force(args.next().expect("arity is verified"));
+    Ok(match b {
+        Builtin::Floor => round(arg()?, int, Ratio::floor, f64::floor)?,
+        Builtin::Round => round(arg()?, int, Ratio::round, f64::round)?,
+        Builtin::ToInt => round(arg()?, int, Ratio::trunc, f64::trunc)?,
+        Builtin::ToFloat => Value::Float(match arg()? {
+            Value::Int(i) => i.to_f64(),
+            Value::Ratio(r) => r.to_f64(),
+            Value::Float(f) => f,
+            v => return Err(v.mismatch("a number").into()),
+        }),
+        Builtin::Ratio => {
+            let (n, d) = (arg()?, arg()?);
+            let (n, d) = (ratio(n, Operator::Div)?, ratio(d, Operator::Div)?);
+            Value::Ratio(Ratio::binary(Operator::Div, &n, &d)?)
+        }
+        Builtin::Show => Value::Str(match arg()? {
+            Value::Str(s) => Rc::from(format!("{:?}", s)),
+            v => {
+                force_deep(&v, force)?;
+                Rc::from(v.to_string())
+            }
+        }),
+        Builtin::Len => {
+            let len = match arg()? {
+                Value::Str(s) => s.chars().count(),
+                mut list @ (Value::Nil | Value::Cons(_)) => {
+                    let mut len = 0;
+                    while let Value::Cons(cell) = list {
+                        len += 1;
+                        list = force(&cell.tail)?;
+                    }
+                    len
+                }
+                v => return Err(v.mismatch("a string").into()),
+            };
+            let len = Int::from_i128(len as i128, int);
+            Value::Int(len.ok_or(ErrorKind::IntConversion)?)
+        }
+        _ => unreachable!("already handled"),
+    })
+}
+
+/// The list cell, fails if the list is empty.
+fn cons(v: Value) -> MiniResult<Rc<Cons>> {
+    match v {
+        Value::Cons(cell) => Ok(cell),
+        Value::Nil => Err(ErrorKind::EmptyList.into()),
+        v => Err(v.mismatch("a list").into()),
+    }
+}
+
+/// Round the number into the integer, by the functions for rationals and floats.
+fn round(
+    v: Value,
+    int: IntType,
+    ratio: fn(Ratio) -> i128,
+    float: fn(f64) -> f64,
+) -> MiniResult<Value> {
+    let i = match v {
+        Value::Int(i) => Some(i),
+        Value::Ratio(r) => Int::from_i128(ratio(r), int),
+        Value::Float(f) => Int::from_f64(float(f), int),
+        v => return Err(v.mismatch("a number").into()),
+    };
+    Ok(Value::Int(i.ok_or(ErrorKind::IntConversion)?))
+}
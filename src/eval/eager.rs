@@ -1,6 +1,10 @@
-use super::{operation, Evaluator, NameSpace};
-use crate::ir::{Expr, Program};
-use crate::{MiniError, MiniResult, Printer};
+use super::{
+    apply, builtin, call_frame, decide, new_frame, operation, unary, Evaluator, Frame, Thunk, Var,
+};
+use crate::ir::{Arm, Decision, Expr, ExprKind, Function, Operator, Program};
+use crate::value::{Closure, Data, Record, Tuple, Value};
+use crate::{MiniError, MiniResult, Numeric, Printer, Span};
+use std::rc::Rc;
 
 /// The eager evaluator
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -9,57 +13,183 @@ pub struct EagerEval;
 impl Evaluator for EagerEval {
     type Err = MiniError;
     fn evaluate<P: Printer>(&self, ir: Program, printer: &mut P) -> Result<(), Self::Err> {
+        let order = ir.binding_order()?;
         let Program {
             funcs,
             vars,
             prints,
+            numeric,
+            ..
         } = ir;
 
-        let mut ns = NameSpace::new();
-        for var in vars {
-            let val = eval_expr(var, &mut ns, &funcs)?;
-            ns.register(val);
+        let globals = vars.iter().map(|_| Var::Empty.thunk()).collect::<Vec<_>>();
+        let env = Env {
+            numeric,
+            funcs: &funcs,
+            globals: &globals,
+        };
+        for id in order {
+            let val = eval_expr(&vars[id], &new_frame(vars[id].frame_size()), &env)?;
+            *globals[id].borrow_mut() = Var::Cached(val);
         }
 
         for print in prints {
-            printer
-                .print(eval_expr(print, &mut ns, &funcs)?)
-                .map_err(MiniError::from_error)?;
+            let val = eval_expr(&print, &new_frame(print.frame_size()), &env)?;
+            printer.print(&val).map_err(MiniError::from_error)?;
         }
         Ok(())
     }
 }
 
-fn funccall(
-    func: Expr,
-    args: Vec<Expr>,
-    ns: &mut NameSpace<i32>,
-    funcs: &[Expr],
-) -> MiniResult<i32> {
-    let depth = ns.chunk();
-    for arg in args {
-        let val = eval_expr(arg, ns, funcs)?;
-        ns.register(val);
-    }
-    let res = eval_expr(func.circulate(depth), ns, funcs)?;
-    ns.back();
-    Ok(res)
+/// The definitions visible from everywhere.
+struct Env<'a> {
+    numeric: Numeric,
+    funcs: &'a [Function],
+    globals: &'a [Thunk],
 }
 
-fn eval_expr(expr: Expr, ns: &mut NameSpace<i32>, funcs: &[Expr]) -> MiniResult<i32> {
-    Ok(match expr {
-        Expr::Value(v) => v,
-        Expr::Variable(depth, id) => *ns.get(depth, id)?,
-        Expr::Operation(op, lhs, rhs) => {
-            operation(op, eval_expr(*lhs, ns, funcs)?, eval_expr(*rhs, ns, funcs)?)?
-        }
-        Expr::FuncCall(f, a) => funccall(funcs[f].clone(), a, ns, funcs)?,
-        Expr::If(c, t, f) => {
-            if eval_expr(*c, ns, funcs)? != 0 {
-                eval_expr(*t, ns, funcs)?
+/// Call the function with evaluated arguments.
+fn call(f: usize, args: Vec<Thunk>, captured: &[Thunk], env: &Env) -> MiniResult<Value> {
+    let frame = call_frame(&env.funcs[f], args, captured);
+    eval_expr(&env.funcs[f].body, &frame, env)
+}
+
+fn eval_args(args: &[Expr], frame: &Frame, env: &Env) -> MiniResult<Vec<Thunk>> {
+    args.iter()
+        .map(|arg| Ok(Var::Cached(eval_expr(arg, frame, env)?).thunk()))
+        .collect()
+}
+
+fn eval_bool(expr: &Expr, frame: &Frame, env: &Env) -> MiniResult<bool> {
+    eval_expr(expr, frame, env)?
+        .bool()
+        .map_err(|e| e.at(expr.span))
+}
+
+// The rarely used or large arms are evaluated by the separate functions, which are never
+// inlined, so that the frame of `eval_expr` stays small for deep recursions.
+fn eval_expr(expr: &Expr, frame: &Frame, env: &Env) -> MiniResult<Value> {
+    let span = expr.span;
+    match &expr.kind {
+        ExprKind::Value(v) => Ok(Value::Int(v.clone())),
+        ExprKind::Float(f) => Ok(Value::Float(*f)),
+        ExprKind::Str(s) => Ok(Value::Str(Rc::clone(s))),
+        ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+        ExprKind::Unit => Ok(Value::Unit),
+        ExprKind::Variable(id) => frame[*id].borrow().cached().map_err(|e| e.at(span)),
+        ExprKind::Global(id) => env.globals[*id].borrow().cached().map_err(|e| e.at(span)),
+        ExprKind::Operation(op, lhs, rhs) => eval_operation(*op, lhs, rhs, span, frame, env),
+        ExprKind::FuncCall(f, a) => call(*f, eval_args(a, frame, env)?, &[], env),
+        ExprKind::Call(f, a) => eval_call(f, a, span, frame, env),
+        ExprKind::If(c, t, f) => {
+            if eval_bool(c, frame, env)? {
+                eval_expr(t, frame, env)
             } else {
-                eval_expr(*f, ns, funcs)?
+                eval_expr(f, frame, env)
             }
         }
+        ExprKind::Let(id, e, b) => {
+            let val = eval_expr(e, frame, env)?;
+            *frame[*id].borrow_mut() = Var::Cached(val);
+            eval_expr(b, frame, env)
+        }
+        ExprKind::Match(d, arms) => {
+            let arm = eval_decision(d, arms, span, frame, env)?;
+            eval_expr(&arms[arm].body, frame, env)
+        }
+        _ => eval_value(expr, frame, env),
+    }
+}
+
+#[inline(never)]
+fn eval_operation(
+    op: Operator,
+    lhs: &Expr,
+    rhs: &Expr,
+    span: Span,
+    frame: &Frame,
+    env: &Env,
+) -> MiniResult<Value> {
+    Ok(match op {
+        Operator::And => Value::Bool(eval_bool(lhs, frame, env)? && eval_bool(rhs, frame, env)?),
+        Operator::Or => Value::Bool(eval_bool(lhs, frame, env)? || eval_bool(rhs, frame, env)?),
+        op => {
+            let lhs = eval_expr(lhs, frame, env)?;
+            let rhs = eval_expr(rhs, frame, env)?;
+            operation(op, lhs, rhs, env.numeric.mode).map_err(|e| e.at(span))?
+        }
+    })
+}
+
+#[inline(never)]
+fn eval_call(f: &Expr, a: &[Expr], span: Span, frame: &Frame, env: &Env) -> MiniResult<Value> {
+    let func = eval_expr(f, frame, env)?.func().map_err(|e| e.at(f.span))?;
+    let args = eval_args(a, frame, env)?;
+    apply(func, args, env.funcs, |f, args, captured| {
+        call(f, args, captured, env)
+    })
+    .map_err(|e| e.at(span))
+}
+
+/// Select the arm of `match`, whose body is evaluated by the caller.
+#[inline(never)]
+fn eval_decision(
+    d: &Decision,
+    arms: &[Arm],
+    span: Span,
+    frame: &Frame,
+    env: &Env,
+) -> MiniResult<usize> {
+    let guard = |arm: usize| match &arms[arm].guard {
+        Some(g) => eval_bool(g, frame, env),
+        None => Ok(true),
+    };
+    decide(d, frame, &|t| t.borrow().cached(), &guard).map_err(|e| e.at(span))
+}
+
+/// Evaluate unary operations, builtins, closures, and the compound values.
+#[inline(never)]
+fn eval_value(expr: &Expr, frame: &Frame, env: &Env) -> MiniResult<Value> {
+    let span = expr.span;
+    Ok(match &expr.kind {
+        ExprKind::Unary(op, e) => {
+            let val = eval_expr(e, frame, env)?;
+            unary(*op, val, env.numeric.mode).map_err(|e| e.at(span))?
+        }
+        ExprKind::Builtin(b, a) => {
+            let args = eval_args(a, frame, env)?;
+            builtin(*b, args, env.numeric.int, &|t| t.borrow().cached()).map_err(|e| e.at(span))?
+        }
+        ExprKind::Closure(f, captured) => Value::Func(Rc::new(Closure {
+            func: *f,
+            captured: captured.iter().map(|id| Rc::clone(&frame[*id])).collect(),
+            args: Vec::new(),
+        })),
+        ExprKind::Tuple(es) => Value::Tuple(Rc::new(Tuple {
+            elems: eval_args(es, frame, env)?,
+        })),
+        ExprKind::Record(fs) => {
+            let fields = fs
+                .iter()
+                .map(|(n, e)| Ok((Rc::clone(n), Var::Cached(eval_expr(e, frame, env)?).thunk())))
+                .collect::<MiniResult<_>>()?;
+            Value::Record(Rc::new(Record { fields }))
+        }
+        ExprKind::Project(e, i) => eval_expr(e, frame, env)?
+            .element(*i)
+            .map_err(|e| e.at(span))?
+            .borrow()
+            .cached()?,
+        ExprKind::Field(e, n) => eval_expr(e, frame, env)?
+            .field(n)
+            .map_err(|e| e.at(span))?
+            .borrow()
+            .cached()?,
+        ExprKind::Construct(c, n, a) => Value::Data(Rc::new(Data {
+            ctor: *c,
+            name: Rc::clone(n),
+            fields: eval_args(a, frame, env)?,
+        })),
+        _ => unreachable!("evaluated by `eval_expr`"),
     })
 }
@@ -0,0 +1,132 @@
+use crate::ir::{Expr, Function};
+use crate::value::{Closure, Value};
+use crate::{ErrorKind, MiniResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The variable slot, shared by the frame and the closures capturing it.
+pub type Thunk = Rc<RefCell<Var>>;
+
+/// The local variables of a function call.
+pub type Frame = Rc<Vec<Thunk>>;
+
+pub enum Var {
+    /// The variable not bound yet.
+    Empty,
+    /// The expression to be evaluated in the frame.
+    Pending(Expr, Frame),
+    /// The variable being evaluated.
+    Forcing,
+    Cached(Value),
+    /// The variable sharing another one, bound by `match`.
+    Alias(Thunk),
+}
+
+impl Var {
+    pub fn thunk(self) -> Thunk {
+        Rc::new(RefCell::new(self))
+    }
+
+    /// The cached value, if evaluated already.
+    pub fn peek(&self) -> Option<Value> {
+        match self {
+            Self::Cached(v) => Some(v.clone()),
+            Self::Alias(t) => t.borrow().peek(),
+            _ => None,
+        }
+    }
+
+    /// The cached value, fails if not evaluated yet.
+    pub fn cached(&self) -> MiniResult<Value> {
+        match self {
+            Self::Cached(v) => Ok(v.clone()),
+            Self::Alias(t) => t.borrow().cached(),
+            _ => Err(ErrorKind::BorrowConflict.into()),
+        }
+    }
+}
+
+/// Make the frame to call `func`, where `args` and `captured` are placed.
+pub fn call_frame(func: &Function, args: Vec<Thunk>, captured: &[Thunk]) -> Frame {
+    let mut frame = args;
+    frame.resize_with(func.frame, || Var::Empty.thunk());
+    for (&id, var) in func.captures.iter().zip(captured) {
+        frame[id] = Rc::clone(var);
+    }
+    Rc::new(frame)
+}
+
+/// Apply `args` to the function value by `call`, which calls the function with exact number
+/// of arguments.
+///
+/// Returns the function waiting for the rest arguments if `args` are not enough, and the
+/// result is applied again if `args` are too many.
+pub fn apply<F>(
+    mut func: Rc<Closure>,
+    mut args: Vec<Thunk>,
+    funcs: &[Function],
+    call: F,
+) -> MiniResult<Value>
+where
+    F: Fn(usize, Vec<Thunk>, &[Thunk]) -> MiniResult<Value>,
+{
+    loop {
+        let arity = funcs[func.func].arity;
+        let mut applied = func.args.clone();
+        applied.append(&mut args);
+        if applied.len() < arity {
+            return Ok(Value::Func(Rc::new(Closure {
+                args: applied,
+                ..(*func).clone()
+            })));
+        }
+        let rest = applied.split_off(arity);
+        let val = call(func.func, applied, &func.captured)?;
+        if rest.is_empty() {
+            return Ok(val);
+        }
+        func = val.func()?;
+        args = rest;
+    }
+}
+
+/// Make the frame with `size` local variables, to evaluate top-level expressions.
+pub fn new_frame(size: usize) -> Frame {
+    Rc::new((0..size).map(|_| Var::Empty.thunk()).collect())
+}
+
+/// Evaluate all elements of the lists, tuples, records, and data values in the value by `force`, to be
+/// printed. (Never returns for infinite lists.)
+pub fn force_deep<F>(val: &Value, force: &F) -> MiniResult<()>
+where
+    F: Fn(&Thunk) -> MiniResult<Value>,
+{
+    let mut val = val.clone();
+    loop {
+        match val {
+            Value::Cons(cell) => {
+                force_deep(&force(&cell.head)?, force)?;
+                val = force(&cell.tail)?;
+            }
+            Value::Tuple(t) => {
+                for elem in &t.elems {
+                    force_deep(&force(elem)?, force)?;
+                }
+                return Ok(());
+            }
+            Value::Record(r) => {
+                for (_, field) in &r.fields {
+                    force_deep(&force(field)?, force)?;
+                }
+                return Ok(());
+            }
+            Value::Data(d) => {
+                for field in &d.fields {
+                    force_deep(&force(field)?, force)?;
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+    }
+}
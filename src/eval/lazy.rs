@@ -1,21 +1,11 @@
-use super::{operation, Evaluator, NameSpace};
-use crate::ir::{Expr, Program};
-use crate::{MiniError, MiniResult, Printer};
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-enum Var {
-    Thunk(Expr),
-    Cached(i32),
-}
-
-impl Var {
-    fn get(self, ns: &mut NameSpace<Self>, funcs: &[Expr]) -> MiniResult<i32> {
-        Ok(match self {
-            Self::Thunk(e) => eval_expr(e, ns, funcs)?,
-            Self::Cached(i) => i,
-        })
-    }
-}
+use super::{
+    apply, builtin, call_frame, decide, force_deep, new_frame, operation, unary, Evaluator, Frame,
+    Thunk, Var,
+};
+use crate::ir::{Arm, Decision, Expr, ExprKind, Function, Operator, Program};
+use crate::value::{Closure, Data, Record, Tuple, Value};
+use crate::{ErrorKind, MiniError, MiniResult, Numeric, Printer, Span};
+use std::rc::Rc;
 
 /// The lazy evaluator
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -28,56 +18,201 @@ impl Evaluator for LazyEval {
             funcs,
             vars,
             prints,
+            numeric,
+            ..
         } = ir;
 
-        let mut ns = NameSpace::new();
-        for var in vars {
-            ns.register(Var::Thunk(var));
-        }
+        let globals = vars
+            .into_iter()
+            .map(|var| {
+                let frame = new_frame(var.frame_size());
+                Var::Pending(var, frame).thunk()
+            })
+            .collect::<Vec<_>>();
+        let env = Env {
+            numeric,
+            funcs: &funcs,
+            globals: &globals,
+        };
 
         for print in prints {
-            printer
-                .print(eval_expr(print, &mut ns, &funcs)?)
-                .map_err(MiniError::from_error)?;
+            let val = eval_expr(&print, &new_frame(print.frame_size()), &env)?;
+            force_deep(&val, &|t| force(t, &env))?;
+            printer.print(&val).map_err(MiniError::from_error)?;
         }
         Ok(())
     }
 }
 
-fn funccall(
-    func: Expr,
-    args: Vec<Expr>,
-    ns: &mut NameSpace<Var>,
-    funcs: &[Expr],
-) -> MiniResult<i32> {
-    let depth = ns.chunk();
-    for arg in args {
-        ns.register(Var::Thunk(arg));
+/// The definitions visible from everywhere.
+struct Env<'a> {
+    numeric: Numeric,
+    funcs: &'a [Function],
+    globals: &'a [Thunk],
+}
+
+/// Call the function, where `args` are registered as thunks.
+fn call(f: usize, args: Vec<Thunk>, captured: &[Thunk], env: &Env) -> MiniResult<Value> {
+    let frame = call_frame(&env.funcs[f], args, captured);
+    eval_expr(&env.funcs[f].body, &frame, env)
+}
+
+/// Make the thunk of the expression, sharing the variables' ones.
+fn thunk(expr: &Expr, frame: &Frame, env: &Env) -> Thunk {
+    match expr.kind {
+        ExprKind::Variable(id) => Rc::clone(&frame[id]),
+        ExprKind::Global(id) => Rc::clone(&env.globals[id]),
+        _ => Var::Pending(expr.clone(), Rc::clone(frame)).thunk(),
     }
-    let res = eval_expr(func.circulate(depth), ns, funcs)?;
-    ns.back();
-    Ok(res)
 }
 
-fn eval_expr(expr: Expr, ns: &mut NameSpace<Var>, funcs: &[Expr]) -> MiniResult<i32> {
-    Ok(match expr {
-        Expr::Value(v) => v,
-        Expr::Variable(depth, id) => {
-            let var = ns.borrow(depth, id)?;
-            let val = var.get(ns, funcs)?;
-            ns.ret(depth, id, Var::Cached(val))?;
-            val
-        }
-        Expr::Operation(op, lhs, rhs) => {
-            operation(op, eval_expr(*lhs, ns, funcs)?, eval_expr(*rhs, ns, funcs)?)?
-        }
-        Expr::FuncCall(f, a) => funccall(funcs[f].clone(), a, ns, funcs)?,
-        Expr::If(c, t, f) => {
-            if eval_expr(*c, ns, funcs)? != 0 {
-                eval_expr(*t, ns, funcs)?
+fn thunks(args: &[Expr], frame: &Frame, env: &Env) -> Vec<Thunk> {
+    args.iter().map(|arg| thunk(arg, frame, env)).collect()
+}
+
+/// Evaluate the variable, and cache the value.
+fn force(var: &Thunk, env: &Env) -> MiniResult<Value> {
+    let val = match var.replace(Var::Forcing) {
+        Var::Pending(e, frame) => eval_expr(&e, &frame, env)?,
+        Var::Cached(v) => v,
+        Var::Alias(t) => force(&t, env)?,
+        _ => return Err(ErrorKind::BorrowConflict.into()),
+    };
+    *var.borrow_mut() = Var::Cached(val.clone());
+    Ok(val)
+}
+
+fn eval_bool(expr: &Expr, frame: &Frame, env: &Env) -> MiniResult<bool> {
+    eval_expr(expr, frame, env)?
+        .bool()
+        .map_err(|e| e.at(expr.span))
+}
+
+// The rarely used or large arms are evaluated by the separate functions, which are never
+// inlined, so that the frame of `eval_expr` stays small for deep recursions.
+fn eval_expr(expr: &Expr, frame: &Frame, env: &Env) -> MiniResult<Value> {
+    let span = expr.span;
+    match &expr.kind {
+        ExprKind::Value(v) => Ok(Value::Int(v.clone())),
+        ExprKind::Float(f) => Ok(Value::Float(*f)),
+        ExprKind::Str(s) => Ok(Value::Str(Rc::clone(s))),
+        ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+        ExprKind::Unit => Ok(Value::Unit),
+        ExprKind::Variable(id) => force(&frame[*id], env).map_err(|e| e.at(span)),
+        ExprKind::Global(id) => force(&env.globals[*id], env).map_err(|e| e.at(span)),
+        ExprKind::Operation(op, lhs, rhs) => eval_operation(*op, lhs, rhs, span, frame, env),
+        ExprKind::FuncCall(f, a) => call(*f, thunks(a, frame, env), &[], env),
+        ExprKind::Call(f, a) => eval_call(f, a, span, frame, env),
+        ExprKind::If(c, t, f) => {
+            if eval_bool(c, frame, env)? {
+                eval_expr(t, frame, env)
             } else {
-                eval_expr(*f, ns, funcs)?
+                eval_expr(f, frame, env)
             }
         }
+        ExprKind::Let(id, e, b) => {
+            *frame[*id].borrow_mut() = Var::Pending((**e).clone(), Rc::clone(frame));
+            eval_expr(b, frame, env)
+        }
+        ExprKind::Match(d, arms) => {
+            let arm = eval_decision(d, arms, span, frame, env)?;
+            eval_expr(&arms[arm].body, frame, env)
+        }
+        _ => eval_value(expr, frame, env),
+    }
+}
+
+#[inline(never)]
+fn eval_operation(
+    op: Operator,
+    lhs: &Expr,
+    rhs: &Expr,
+    span: Span,
+    frame: &Frame,
+    env: &Env,
+) -> MiniResult<Value> {
+    Ok(match op {
+        Operator::And => Value::Bool(eval_bool(lhs, frame, env)? && eval_bool(rhs, frame, env)?),
+        Operator::Or => Value::Bool(eval_bool(lhs, frame, env)? || eval_bool(rhs, frame, env)?),
+        op => {
+            let lhs = eval_expr(lhs, frame, env)?;
+            let rhs = eval_expr(rhs, frame, env)?;
+            operation(op, lhs, rhs, env.numeric.mode).map_err(|e| e.at(span))?
+        }
+    })
+}
+
+#[inline(never)]
+fn eval_call(f: &Expr, a: &[Expr], span: Span, frame: &Frame, env: &Env) -> MiniResult<Value> {
+    let func = eval_expr(f, frame, env)?.func().map_err(|e| e.at(f.span))?;
+    apply(
+        func,
+        thunks(a, frame, env),
+        env.funcs,
+        |f, args, captured| call(f, args, captured, env),
+    )
+    .map_err(|e| e.at(span))
+}
+
+/// Select the arm of `match`, whose body is evaluated by the caller.
+#[inline(never)]
+fn eval_decision(
+    d: &Decision,
+    arms: &[Arm],
+    span: Span,
+    frame: &Frame,
+    env: &Env,
+) -> MiniResult<usize> {
+    let guard = |arm: usize| match &arms[arm].guard {
+        Some(g) => eval_bool(g, frame, env),
+        None => Ok(true),
+    };
+    decide(d, frame, &|t| force(t, env), &guard).map_err(|e| e.at(span))
+}
+
+/// Evaluate unary operations, builtins, closures, and the compound values.
+#[inline(never)]
+fn eval_value(expr: &Expr, frame: &Frame, env: &Env) -> MiniResult<Value> {
+    let span = expr.span;
+    Ok(match &expr.kind {
+        ExprKind::Unary(op, e) => {
+            let val = eval_expr(e, frame, env)?;
+            unary(*op, val, env.numeric.mode).map_err(|e| e.at(span))?
+        }
+        ExprKind::Builtin(b, a) => {
+            let args = thunks(a, frame, env);
+            builtin(*b, args, env.numeric.int, &|t| force(t, env)).map_err(|e| e.at(span))?
+        }
+        ExprKind::Closure(f, captured) => Value::Func(Rc::new(Closure {
+            func: *f,
+            captured: captured.iter().map(|id| Rc::clone(&frame[*id])).collect(),
+            args: Vec::new(),
+        })),
+        ExprKind::Tuple(es) => Value::Tuple(Rc::new(Tuple {
+            elems: thunks(es, frame, env),
+        })),
+        ExprKind::Record(fs) => {
+            let fields = fs
+                .iter()
+                .map(|(n, e)| (Rc::clone(n), thunk(e, frame, env)))
+                .collect();
+            Value::Record(Rc::new(Record { fields }))
+        }
+        ExprKind::Project(e, i) => {
+            let val = eval_expr(e, frame, env)?;
+            let elem = val.element(*i).map_err(|e| e.at(span))?;
+            force(&elem, env).map_err(|e| e.at(span))?
+        }
+        ExprKind::Field(e, n) => {
+            let val = eval_expr(e, frame, env)?;
+            let field = val.field(n).map_err(|e| e.at(span))?;
+            force(&field, env).map_err(|e| e.at(span))?
+        }
+        ExprKind::Construct(c, n, a) => Value::Data(Rc::new(Data {
+            ctor: *c,
+            name: Rc::clone(n),
+            fields: thunks(a, frame, env),
+        })),
+        _ => unreachable!("evaluated by `eval_expr`"),
     })
 }
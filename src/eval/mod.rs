@@ -1,13 +1,19 @@
+mod builtin;
 mod eager;
+mod frame;
 mod lazy;
-mod namespace;
 mod operation;
+mod pattern;
 
 pub use eager::EagerEval;
 pub use lazy::LazyEval;
 
-use namespace::NameSpace;
-use operation::operation;
+use builtin::builtin;
+use frame::{apply, call_frame, force_deep, new_frame, Frame};
+use operation::{operation, unary};
+use pattern::decide;
+
+pub(crate) use frame::{Thunk, Var};
 
 use crate::ir::Program;
 use crate::Printer;
@@ -15,7 +21,7 @@ use crate::Printer;
 /// The trait to provide kinds of evaluation (or compilation).
 pub trait Evaluator {
     /// The error type that evaluator will provide.
-    type Err: std::error::Error + 'static;
+    type Err: std::error::Error + Send + Sync + 'static;
     /// Evaluate `Program` and print expression by `printer`.
     fn evaluate<P: Printer>(&self, ir: Program, printer: &mut P) -> Result<(), Self::Err>;
 }
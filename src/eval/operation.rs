@@ -1,58 +1,198 @@
-use crate::ir::Operator;
-use crate::MiniResult;
-
-pub fn operation(op: Operator, lhs: i32, rhs: i32) -> MiniResult<i32> {
-    Ok(match op {
-        Operator::Add => lhs.checked_add(rhs).ok_or("Overflowed addition")?,
-        Operator::Sub => lhs.checked_sub(rhs).ok_or("Overflowed subtraction")?,
-        Operator::Mul => lhs.checked_mul(rhs).ok_or("Overflowed multiplication")?,
-        Operator::Div => lhs
-            .checked_div(rhs)
-            .ok_or("Overflowed division, or division by zero")?,
-        Operator::Rem => lhs
-            .checked_rem(rhs)
-            .ok_or("Overflowed remainder, or division by zero")?,
-        Operator::Gt => {
-            if lhs > rhs {
-                1
-            } else {
-                0
-            }
+use crate::ir::{Operator, UnaryOperator};
+use crate::{ArithMode, ErrorKind, Int, MiniResult, Ratio, Value};
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+pub fn operation(op: Operator, lhs: Value, rhs: Value, mode: ArithMode) -> MiniResult<Value> {
+    match op {
+        // Numbers of different kinds are compared after the promotion.
+        Operator::Eq | Operator::Neq if rank(&lhs).is_none() || rank(&rhs).is_none() => {
+            return Ok(Value::Bool(equal(lhs, rhs)? == (op == Operator::Eq)))
         }
-        Operator::Ge => {
-            if lhs >= rhs {
-                1
-            } else {
-                0
-            }
+        // Evaluators short-circuit these operators, without calling this function.
+        Operator::And => return Ok(Value::Bool(lhs.bool()? && rhs.bool()?)),
+        Operator::Or => return Ok(Value::Bool(lhs.bool()? || rhs.bool()?)),
+        _ => {}
+    }
+
+    if let (Value::Str(l), Value::Str(r)) = (&lhs, &rhs) {
+        if op == Operator::Concat {
+            return Ok(Value::Str(Rc::from(format!("{}{}", l, r))));
         }
-        Operator::Lt => {
-            if lhs < rhs {
-                1
-            } else {
-                0
-            }
+        // Lexicographically, by the code points.
+        if let Some(b) = compare(op, l.cmp(r)) {
+            return Ok(Value::Bool(b));
+        }
+    }
+    if op == Operator::Concat {
+        let v = if let Value::Str(_) = lhs { rhs } else { lhs };
+        return Err(v.mismatch("a string").into());
+    }
+
+    // Bitwise operators on booleans are logical operators without short-circuit.
+    if let (Value::Bool(l), Value::Bool(r)) = (&lhs, &rhs) {
+        match op {
+            Operator::BitAnd => return Ok(Value::Bool(l & r)),
+            Operator::BitOr => return Ok(Value::Bool(l | r)),
+            Operator::BitXor => return Ok(Value::Bool(l ^ r)),
+            _ => {}
         }
-        Operator::Le => {
-            if lhs <= rhs {
-                1
-            } else {
-                0
+    }
+
+    let (lrank, rrank) = match (rank(&lhs), rank(&rhs)) {
+        (Some(l), Some(r)) => (l, r),
+        (Some(_), None) => return Err(rhs.mismatch(lhs.type_name()).into()),
+        (None, Some(_)) => return Err(lhs.mismatch(rhs.type_name()).into()),
+        (None, None) => return Err(lhs.mismatch("an integer").into()),
+    };
+    if lrank.max(rrank) > 0 && is_integral(op) {
+        let v = if lrank > 0 { lhs } else { rhs };
+        return Err(v.mismatch("an integer").into());
+    }
+
+    Ok(match lrank.max(rrank) {
+        0 => {
+            let (lhs, rhs) = (lhs.int()?, rhs.int()?);
+            match compare(op, lhs.cmp(&rhs)) {
+                Some(b) => Value::Bool(b),
+                None => Value::Int(Int::binary(op, &lhs, &rhs, mode)?),
             }
         }
-        Operator::Eq => {
-            if lhs == rhs {
-                1
-            } else {
-                0
+        1 => {
+            let (lhs, rhs) = (ratio(lhs, op)?, ratio(rhs, op)?);
+            match op {
+                // Rationals are always in the lowest terms.
+                Operator::Eq => Value::Bool(lhs == rhs),
+                Operator::Neq => Value::Bool(lhs != rhs),
+                Operator::Gt | Operator::Ge | Operator::Lt | Operator::Le => {
+                    let ord = lhs.checked_cmp(&rhs).ok_or(ErrorKind::Overflow { op })?;
+                    Value::Bool(compare(op, ord).unwrap_or_default())
+                }
+                op => Value::Ratio(Ratio::binary(op, &lhs, &rhs)?),
             }
         }
-        Operator::Neq => {
-            if lhs != rhs {
-                1
-            } else {
-                0
+        _ => {
+            let (l, r) = (float(lhs), float(rhs));
+            match op {
+                // Every comparison with `NaN` is false, except `!=`.
+                Operator::Gt => Value::Bool(l > r),
+                Operator::Ge => Value::Bool(l >= r),
+                Operator::Lt => Value::Bool(l < r),
+                Operator::Le => Value::Bool(l <= r),
+                Operator::Eq => Value::Bool(l == r),
+                Operator::Neq => Value::Bool(l != r),
+                Operator::Add => Value::Float(l + r),
+                Operator::Sub => Value::Float(l - r),
+                Operator::Mul => Value::Float(l * r),
+                Operator::Div => Value::Float(l / r),
+                // Floored, like integers.
+                Operator::Rem => {
+                    let m = l % r;
+                    Value::Float(if m != 0.0 && (m < 0.0) != (r < 0.0) {
+                        m + r
+                    } else {
+                        m
+                    })
+                }
+                Operator::Pow => Value::Float(l.powf(r)),
+                _ => unreachable!("not a float operator"),
             }
         }
     })
 }
+
+pub fn unary(op: UnaryOperator, val: Value, mode: ArithMode) -> MiniResult<Value> {
+    Ok(match (op, val) {
+        (UnaryOperator::Not, val) => Value::Bool(!val.bool()?),
+        (UnaryOperator::Neg, Value::Float(f)) => Value::Float(-f),
+        (UnaryOperator::Neg, Value::Ratio(r)) => {
+            Value::Ratio(r.checked_neg().ok_or(ErrorKind::UnaryOverflow { op })?)
+        }
+        (op, val) => Value::Int(Int::unary(op, &val.int()?, mode)?),
+    })
+}
+
+/// The order of the numeric promotion: integers, rationals, and floats.
+fn rank(v: &Value) -> Option<u8> {
+    match v {
+        Value::Int(_) => Some(0),
+        Value::Ratio(_) => Some(1),
+        Value::Float(_) => Some(2),
+        _ => None,
+    }
+}
+
+/// The operators only for integers.
+fn is_integral(op: Operator) -> bool {
+    matches!(
+        op,
+        Operator::TruncDiv
+            | Operator::TruncRem
+            | Operator::EuclidDiv
+            | Operator::EuclidRem
+            | Operator::Shl
+            | Operator::Shr
+            | Operator::BitAnd
+            | Operator::BitOr
+            | Operator::BitXor
+    )
+}
+
+/// Apply the comparison operator to the ordering, or `None` for other operators.
+fn compare(op: Operator, ord: Ordering) -> Option<bool> {
+    match op {
+        Operator::Gt => Some(ord == Ordering::Greater),
+        Operator::Ge => Some(ord != Ordering::Less),
+        Operator::Lt => Some(ord == Ordering::Less),
+        Operator::Le => Some(ord != Ordering::Greater),
+        Operator::Eq => Some(ord == Ordering::Equal),
+        Operator::Neq => Some(ord != Ordering::Equal),
+        _ => None,
+    }
+}
+
+/// Promote the integer or the rational into the rational.
+pub(crate) fn ratio(v: Value, op: Operator) -> Result<Ratio, ErrorKind> {
+    match v {
+        Value::Int(i) => Ok(Ratio::from(i.to_i128().ok_or(ErrorKind::Overflow { op })?)),
+        Value::Ratio(r) => Ok(r),
+        v => Err(v.mismatch("a rational")),
+    }
+}
+
+/// Promote the number into the float.
+fn float(v: Value) -> f64 {
+    match v {
+        Value::Int(i) => i.to_f64(),
+        Value::Ratio(r) => r.to_f64(),
+        Value::Float(f) => f,
+        _ => unreachable!("not a number"),
+    }
+}
+
+/// Whether `==` can compare the value. Compound values would have to be evaluated, and lists
+/// may be infinite. (use `is_nil` or `match` instead.)
+fn is_comparable(v: &Value) -> bool {
+    !matches!(
+        v,
+        Value::Func(_)
+            | Value::Nil
+            | Value::Cons(_)
+            | Value::Tuple(_)
+            | Value::Record(_)
+            | Value::Data(_)
+    )
+}
+
+/// Compare the values of the same type, except functions and compound values.
+fn equal(lhs: Value, rhs: Value) -> MiniResult<bool> {
+    Ok(match (lhs, rhs) {
+        (Value::Str(l), Value::Str(r)) => l == r,
+        (Value::Bool(l), Value::Bool(r)) => l == r,
+        (Value::Unit, Value::Unit) => true,
+        (f, _) | (_, f) if !is_comparable(&f) => {
+            return Err(f.mismatch("a comparable value").into())
+        }
+        (l, r) => return Err(r.mismatch(l.type_name()).into()),
+    })
+}
@@ -0,0 +1,74 @@
+use super::{Frame, Thunk, Var};
+use crate::ir::{Case, Decision};
+use crate::{MiniResult, Value};
+use std::rc::Rc;
+
+/// Follow the decision tree of `match`, testing the local variables evaluated by `force` and
+/// the guards of arms by `guard`, and return the index of the arm to be evaluated.
+pub fn decide<F, G>(
+    mut decision: &Decision,
+    frame: &Frame,
+    force: &F,
+    guard: &G,
+) -> MiniResult<usize>
+where
+    F: Fn(&Thunk) -> MiniResult<Value>,
+    G: Fn(usize) -> MiniResult<bool>,
+{
+    loop {
+        match decision {
+            Decision::Arm(arm) => return Ok(*arm),
+            Decision::Guard(arm, otherwise) => {
+                if guard(*arm)? {
+                    return Ok(*arm);
+                }
+                decision = otherwise;
+            }
+            Decision::Switch(var, cases, default) => {
+                let val = force(&frame[*var])?;
+                decision = select(&val, cases, default.as_deref(), frame)?;
+            }
+            Decision::Fail => unreachable!("rejected by the exhaustiveness check"),
+        }
+    }
+}
+
+/// Take the first case matching the value, binding its parts into the local variables without
+/// evaluating them.
+fn select<'a>(
+    val: &Value,
+    cases: &'a [(Case, Vec<usize>, Decision)],
+    default: Option<&'a Decision>,
+    frame: &Frame,
+) -> MiniResult<&'a Decision> {
+    for (case, fields, decision) in cases {
+        let parts: &[Thunk] = match (case, val) {
+            (Case::Constructor(id), Value::Data(d)) if d.ctor == *id => &d.fields,
+            (Case::Tuple(n), Value::Tuple(t)) if t.elems.len() == *n => &t.elems,
+            (Case::Value(i), Value::Int(v)) if i == v => &[],
+            (Case::Str(s), Value::Str(v)) if s == v => &[],
+            (Case::Bool(b), Value::Bool(v)) if b == v => &[],
+            _ => continue,
+        };
+        for (&id, part) in fields.iter().zip(parts) {
+            *frame[id].borrow_mut() = Var::Alias(Rc::clone(part));
+        }
+        return Ok(decision);
+    }
+    match (default, cases.first()) {
+        (Some(decision), _) => Ok(decision),
+        // The cases cover all values of their type, so the value has another type.
+        (None, Some((case, _, _))) => Err(val.mismatch(expected(case)).into()),
+        (None, None) => unreachable!("`Switch` without cases has the default"),
+    }
+}
+
+fn expected(case: &Case) -> &'static str {
+    match case {
+        Case::Constructor(_) => "a data value",
+        Case::Value(_) => "an integer",
+        Case::Str(_) => "a string",
+        Case::Bool(_) => "a boolean",
+        Case::Tuple(_) => "a tuple",
+    }
+}
@@ -1,118 +1,1535 @@
-use crate::{parser, MiniError, MiniResult};
+//! The intermediate representation, compiled from the syntax tree.
+use crate::{parser, ErrorKind, Int, MiniError, MiniResult, Numeric, Span, Warning};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::rc::Rc;
 
-pub use parser::Operator;
+pub use parser::{Operator, UnaryOperator};
 
 /// List of define functions, variables, and expressions to print.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct Program {
-    pub funcs: Vec<Expr>,
+    pub funcs: Vec<Function>,
+    /// The number of functions defined by `def`, which are placed before the lifted ones in
+    /// `funcs`.
+    pub defined: usize,
     pub vars: Vec<Expr>,
+    /// The names of `vars`.
+    pub var_names: Vec<String>,
     pub prints: Vec<Expr>,
+    /// The data types declared by `data`.
+    pub datas: Vec<DataType>,
+    /// The constructors of all data types.
+    pub ctors: Vec<Constructor>,
+    /// The problems which don't prevent the evaluation, like unreachable clauses.
+    pub warnings: Vec<Warning>,
+    /// The integer semantics, which the literals are already converted into.
+    pub numeric: Numeric,
+}
+
+/// The function defined by `def`, or lifted from the lambda expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Function {
+    /// The function's name. (`<lambda>` for lambda expressions.)
+    pub name: String,
+    /// The number of arguments, which are placed at the head of the frame.
+    pub arity: usize,
+    /// The local variables where the captured values are placed.
+    pub captures: Vec<usize>,
+    /// The number of local variables. (including the arguments, and the captured values.)
+    pub frame: usize,
+    pub body: Expr,
+    /// The location of the definition.
+    pub span: Span,
+}
+
+/// The data type declared by `data`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataType {
+    pub name: String,
+    /// The constructors' indices (in `program.ctors`), in the order of the declaration.
+    pub ctors: Vec<usize>,
+    /// The location of the declaration.
+    pub span: Span,
 }
 
-/// The expression tree.
+/// The constructor of the data type.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Expr {
-    /// The literal value.
-    Value(i32),
-    /// The variable's scope depth (specified by `expr.circulate`), and index (in `program.vars`).
-    Variable(usize, usize),
+#[non_exhaustive]
+pub struct Constructor {
+    pub name: Rc<str>,
+    /// The number of fields.
+    pub arity: usize,
+    /// The data type's index (in `program.datas`).
+    pub data: usize,
+    /// The types of the fields, which are checked only by `types::infer`.
+    pub fields: Vec<parser::TypeExpr>,
+}
+
+/// The expression tree, located by the span in the source code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+/// The kinds of expression.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ExprKind {
+    /// The integer literal.
+    Value(Int),
+    /// The float literal.
+    Float(f64),
+    /// The string literal.
+    Str(Rc<str>),
+    /// The boolean literal.
+    Bool(bool),
+    /// The unit value.
+    Unit,
+    /// The local variable's index (in the frame of the function).
+    Variable(usize),
+    /// The global variable's index (in `program.vars`).
+    Global(usize),
     /// The operator, and left side value, and right side value.
     Operation(Operator, Box<Expr>, Box<Expr>),
+    /// The prefix operator, and the operand.
+    Unary(UnaryOperator, Box<Expr>),
     /// The function index (in `program.funcs`) and list of arguments (number integrity is already verified.)
     FuncCall(usize, Vec<Expr>),
+    /// The builtin function, and list of arguments. (number integrity is already verified.)
+    Builtin(Builtin, Vec<Expr>),
+    /// The function value to be called, and list of arguments.
+    Call(Box<Expr>, Vec<Expr>),
+    /// The function index (in `program.funcs`), and the local variables captured by the function
+    /// value. (in the order of `func.captures`)
+    Closure(usize, Vec<usize>),
     /// The condition, the expression evaluated if condition is true, and false.
     If(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// The local variable's index (same as `Variable`), the bound value, and the expression
+    /// which the variable is visible from.
+    Let(usize, Box<Expr>, Box<Expr>),
+    /// The elements of the tuple.
+    Tuple(Vec<Expr>),
+    /// The names and values of the record's fields. (names are already verified to be unique.)
+    Record(Vec<(Rc<str>, Expr)>),
+    /// The tuple, and the index of the element.
+    Project(Box<Expr>, usize),
+    /// The record, and the name of the field.
+    Field(Box<Expr>, Rc<str>),
+    /// The constructor index (in `program.ctors`), its name, and the fields. (number integrity
+    /// is already verified.)
+    Construct(usize, Rc<str>, Vec<Expr>),
+    /// The decision tree testing the local variables, and the arms. (`match`, or the function
+    /// defined by clauses.)
+    Match(Box<Decision>, Vec<Arm>),
 }
 
-impl Expr {
-    fn from_ast(
-        e: parser::Expr,
-        ns_vars: &HashMap<String, usize>,
-        ns_funcs: &HashMap<String, (usize, usize)>,
-    ) -> MiniResult<Self> {
-        Ok(match e {
-            parser::Expr::Value(v) => Self::Value(v),
-            parser::Expr::Variable(s) => {
-                Self::Variable(0, *ns_vars.get(&s).ok_or("Using undefined variable.")?)
-            }
-            parser::Expr::Operation(op, lhs, rhs) => Self::Operation(
+/// The arm of `match`, or the clause of the function. (Clauses with guards are split into the
+/// arms of each guard.)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Arm {
+    /// The condition tested after the patterns matched.
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+/// The decision tree of `match`, which tests each part of the value at most once.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decision {
+    /// Evaluate the arm. (the index in the arms of `match`)
+    Arm(usize),
+    /// Evaluate the arm if its guard is true, or follow the decision otherwise.
+    Guard(usize, Box<Decision>),
+    /// Test the local variable by the cases in order, binding the fields of the matched one
+    /// into the local variables, or take the default if no case matches.
+    Switch(
+        usize,
+        Vec<(Case, Vec<usize>, Decision)>,
+        Option<Box<Decision>>,
+    ),
+    /// No arm matches, which is rejected by the exhaustiveness check.
+    Fail,
+}
+
+/// The shapes of values tested by `Decision::Switch`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Case {
+    /// The constructor index (in `program.ctors`), whose fields are bound.
+    Constructor(usize),
+    Value(Int),
+    Str(Rc<str>),
+    Bool(bool),
+    /// The tuple with the number of elements, which are bound.
+    Tuple(usize),
+}
+
+/// The functions provided by the language, which can be shadowed by the definitions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Builtin {
+    /// `floor(x)`, rounds the number toward negative infinity.
+    Floor,
+    /// `round(x)`, rounds the number to the nearest integer, and half-way cases away from zero.
+    Round,
+    /// `to_int(x)`, rounds the number toward zero.
+    ToInt,
+    /// `to_float(x)`, converts the number into the float.
+    ToFloat,
+    /// `ratio(n, d)`, makes the rational number `n/d` from integers or rationals.
+    Ratio,
+    /// `show(x)`, converts the value into the string, quoting strings.
+    Show,
+    /// `len(s)`, the number of characters in the string, or the number of elements in the list.
+    Len,
+    /// `nil`, the empty list.
+    Nil,
+    /// `cons(x, xs)`, makes the list cell, without evaluating `x` and `xs` in `LazyEval`.
+    Cons,
+    /// `head(xs)`, the first element of the list.
+    Head,
+    /// `tail(xs)`, the list without the first element.
+    Tail,
+    /// `is_nil(xs)`, whether the list is empty.
+    IsNil,
+}
+
+impl Builtin {
+    const ALL: [Self; 12] = [
+        Self::Floor,
+        Self::Round,
+        Self::ToInt,
+        Self::ToFloat,
+        Self::Ratio,
+        Self::Show,
+        Self::Len,
+        Self::Nil,
+        Self::Cons,
+        Self::Head,
+        Self::Tail,
+        Self::IsNil,
+    ];
+
+    /// The name to call the function.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Floor => "floor",
+            Self::Round => "round",
+            Self::ToInt => "to_int",
+            Self::ToFloat => "to_float",
+            Self::Ratio => "ratio",
+            Self::Show => "show",
+            Self::Len => "len",
+            Self::Nil => "nil",
+            Self::Cons => "cons",
+            Self::Head => "head",
+            Self::Tail => "tail",
+            Self::IsNil => "is_nil",
+        }
+    }
+
+    /// The number of arguments.
+    pub fn arity(self) -> usize {
+        match self {
+            Self::Nil => 0,
+            Self::Ratio | Self::Cons => 2,
+            _ => 1,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|b| b.name() == name)
+    }
+}
+
+/// The names visible from an expression.
+struct Scope<'a> {
+    /// The arguments of the function, and the variables bound by `let ... in`.
+    locals: HashMap<String, usize>,
+    /// The number of local variables used in the function.
+    frame: &'a Cell<usize>,
+    /// The variables captured from the enclosing scope, with their index in the enclosing
+    /// frame, and in the current frame.
+    captures: &'a RefCell<Vec<(String, usize, usize)>>,
+    /// The scope enclosing the lambda expression.
+    parent: Option<&'a Scope<'a>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new_local(&self) -> usize {
+        let id = self.frame.get();
+        self.frame.set(id + 1);
+        id
+    }
+
+    /// Find the local variable, capturing it from the enclosing scopes if needed.
+    fn lookup(&self, name: &str) -> Option<usize> {
+        if let Some(id) = self.locals.get(name) {
+            return Some(*id);
+        }
+        if let Some((_, _, id)) = self.captures.borrow().iter().find(|(n, _, _)| n == name) {
+            return Some(*id);
+        }
+        let outer = self.parent?.lookup(name)?;
+        let id = self.new_local();
+        self.captures
+            .borrow_mut()
+            .push((String::from(name), outer, id));
+        Some(id)
+    }
+}
+
+/// The binding of the global variable by `let`.
+#[derive(Copy, Clone)]
+struct Binding {
+    /// The position of the statement.
+    position: usize,
+    /// The index in `program.vars`.
+    id: usize,
+    span: Span,
+}
+
+/// The state of compilation.
+struct Compiler<'a> {
+    numeric: Numeric,
+    /// The global variables, which may be bound more than once.
+    globals: &'a HashMap<String, Vec<Binding>>,
+    /// The position of the statement, which chooses the binding of the global variables.
+    position: usize,
+    /// The functions, with their index, arity, and location.
+    funcs: &'a HashMap<String, (usize, usize, Span)>,
+    /// The constructors' indices, by their names.
+    ctor_names: &'a HashMap<String, usize>,
+    ctors: &'a [Constructor],
+    datas: &'a [DataType],
+    /// The functions lifted from lambda expressions, which are placed after the defined ones.
+    lambdas: Vec<Function>,
+    errors: Vec<MiniError>,
+    warnings: Vec<Warning>,
+}
+
+/// The pattern resolved by the compiler.
+#[derive(Clone)]
+enum Pat {
+    /// The wildcard, or the variable to be bound.
+    Any(Option<String>),
+    Case(Case, Vec<Pat>),
+}
+
+/// The row of the pattern matrix, which are the patterns to test the values, and the arm
+/// evaluated if all of them match.
+#[derive(Clone)]
+struct Row {
+    pats: Vec<Pat>,
+    arm: usize,
+}
+
+/// The state of compiling a `match`.
+struct Matcher {
+    /// The local variables where the parts of the value are bound, by the local variable of
+    /// the enclosing value, its case, and the position in it.
+    fields: HashMap<(usize, Case, usize), usize>,
+    /// The cases taken to reach the current node of the decision tree.
+    path: Vec<(usize, Case)>,
+    /// The local variables bound by the variable patterns of each arm.
+    bindings: Vec<HashMap<String, usize>>,
+    /// Whether each arm has the guard.
+    guarded: Vec<bool>,
+    /// Whether each arm appears in the decision tree.
+    reached: Vec<bool>,
+    /// The local variables tested first.
+    roots: Vec<usize>,
+    /// The values not covered by any arm, found first. (in the order of `roots`)
+    missing: Option<Vec<String>>,
+}
+
+/// The clause of the function, or the arm of `match`.
+struct Clause {
+    pats: Vec<parser::Pattern>,
+    guards: Vec<(Option<parser::Expr>, parser::Expr)>,
+    span: Span,
+}
+
+impl Clause {
+    /// The clause of `def` without patterns nor guards.
+    fn plain(args: Vec<String>, body: parser::Expr, span: Span) -> Self {
+        let pats = args
+            .into_iter()
+            .map(|a| parser::Pattern {
+                kind: parser::PatternKind::Variable(a),
+                span,
+            })
+            .collect();
+        Self {
+            pats,
+            guards: vec![(None, body)],
+            span,
+        }
+    }
+}
+
+/// The top-level statement, where the clauses of a function are merged.
+enum Item {
+    Stmt(parser::Stmt),
+    /// The function's name, and its clauses.
+    Clauses(String, Vec<Clause>),
+}
+
+impl Matcher {
+    fn field(&mut self, var: usize, case: &Case, index: usize, scope: &Scope) -> usize {
+        let key = (var, case.clone(), index);
+        if let Some(id) = self.fields.get(&key) {
+            return *id;
+        }
+        let id = scope.new_local();
+        self.fields.insert(key, id);
+        id
+    }
+
+    fn bind(&mut self, arm: usize, pat: &Pat, var: usize) {
+        if let Pat::Any(Some(name)) = pat {
+            self.bindings[arm].insert(name.clone(), var);
+        }
+    }
+}
+
+impl<'a> Compiler<'a> {
+    /// Convert the AST, collecting every error into `self.errors`.
+    /// (The erroneous expressions are replaced by dummy values.)
+    fn expr(&mut self, e: parser::Expr, scope: &Scope) -> Expr {
+        let span = e.span;
+        let kind = match e.kind {
+            parser::ExprKind::Value(v) => match Int::parse(&v, self.numeric.int) {
+                Some(i) => ExprKind::Value(i),
+                None => {
+                    self.error(ErrorKind::LiteralOutOfRange { literal: v }, span);
+                    ExprKind::Unit
+                }
+            },
+            parser::ExprKind::Float(v) => ExprKind::Float(v.parse().expect("float literal")),
+            parser::ExprKind::Str(s) => ExprKind::Str(Rc::from(s)),
+            parser::ExprKind::Bool(b) => ExprKind::Bool(b),
+            parser::ExprKind::Unit => ExprKind::Unit,
+            parser::ExprKind::Variable(s) => match self.variable(&s, scope, span) {
+                Some(kind) => kind,
+                None => {
+                    self.error(ErrorKind::UndefinedVariable { name: s }, span);
+                    ExprKind::Unit
+                }
+            },
+            parser::ExprKind::Operation(op, lhs, rhs) => ExprKind::Operation(
                 op,
-                Box::new(Self::from_ast(*lhs, ns_vars, ns_funcs)?),
-                Box::new(Self::from_ast(*rhs, ns_vars, ns_funcs)?),
+                Box::new(self.expr(*lhs, scope)),
+                Box::new(self.expr(*rhs, scope)),
             ),
-            parser::Expr::FuncCall(s, e) => {
-                let (id, args) = *ns_funcs.get(&s).ok_or("Using undefined function.")?;
-                if e.len() != args {
-                    return Err(MiniError::from("Illegal arguments."));
-                }
-                Self::FuncCall(
-                    id,
-                    e.into_iter()
-                        .map(|e| Self::from_ast(e, ns_vars, ns_funcs))
-                        .collect::<Result<Vec<_>, _>>()?,
-                )
+            parser::ExprKind::Unary(op, e) => ExprKind::Unary(op, Box::new(self.expr(*e, scope))),
+            parser::ExprKind::FuncCall(s, e) => {
+                let args = e
+                    .into_iter()
+                    .map(|e| self.expr(e, scope))
+                    .collect::<Vec<_>>();
+                let callee = scope
+                    .lookup(&s)
+                    .map(ExprKind::Variable)
+                    .or_else(|| self.global(&s, span).map(ExprKind::Global));
+                match (callee, self.funcs.get(&s), Builtin::from_name(&s)) {
+                    (Some(kind), _, _) => ExprKind::Call(Box::new(Expr { kind, span }), args),
+                    (None, Some(&(id, arity, _)), _) if args.len() == arity => {
+                        ExprKind::FuncCall(id, args)
+                    }
+                    // Partial application, or over-application.
+                    (None, Some(&(id, _, _)), _) => {
+                        let kind = ExprKind::Closure(id, Vec::new());
+                        ExprKind::Call(Box::new(Expr { kind, span }), args)
+                    }
+                    (None, None, Some(b)) if args.len() == b.arity() => ExprKind::Builtin(b, args),
+                    (None, None, Some(b)) => {
+                        let kind = self.builtin(b, span);
+                        ExprKind::Call(Box::new(Expr { kind, span }), args)
+                    }
+                    (None, None, None) => {
+                        self.error(ErrorKind::UndefinedFunction { name: s }, span);
+                        ExprKind::Unit
+                    }
+                }
+            }
+            parser::ExprKind::Call(f, e) => {
+                let args = e
+                    .into_iter()
+                    .map(|e| self.expr(e, scope))
+                    .collect::<Vec<_>>();
+                let ctor = match &f.kind {
+                    parser::ExprKind::Constructor(n) => self.ctor_names.get(n).copied(),
+                    _ => None,
+                };
+                match ctor {
+                    Some(id) if args.len() == self.ctors[id].arity => {
+                        ExprKind::Construct(id, Rc::clone(&self.ctors[id].name), args)
+                    }
+                    _ => ExprKind::Call(Box::new(self.expr(*f, scope)), args),
+                }
             }
-            parser::Expr::If(c, t, f) => Self::If(
-                Box::new(Self::from_ast(*c, ns_vars, ns_funcs)?),
-                Box::new(Self::from_ast(*t, ns_vars, ns_funcs)?),
-                Box::new(Self::from_ast(*f, ns_vars, ns_funcs)?),
+            parser::ExprKind::Lambda(a, b) => {
+                let id = self.funcs.len() + self.lambdas.len();
+                // Reserve the index, for the lambdas in the body.
+                self.lambdas.push(Function {
+                    name: String::from("<lambda>"),
+                    arity: a.len(),
+                    captures: Vec::new(),
+                    frame: 0,
+                    body: Expr {
+                        kind: ExprKind::Unit,
+                        span,
+                    },
+                    span,
+                });
+                let (func, captured) = self.function(String::from("<lambda>"), a, *b, span, scope);
+                self.lambdas[id - self.funcs.len()] = func;
+                ExprKind::Closure(id, captured)
+            }
+            parser::ExprKind::If(c, t, f) => ExprKind::If(
+                Box::new(self.expr(*c, scope)),
+                Box::new(self.expr(*t, scope)),
+                Box::new(self.expr(*f, scope)),
             ),
-        })
+            parser::ExprKind::Let(v, e, b) => {
+                let e = self.expr(*e, scope);
+                let id = scope.new_local();
+                let mut locals = scope.locals.clone();
+                locals.insert(v, id);
+                let inner = Scope {
+                    locals,
+                    frame: scope.frame,
+                    captures: scope.captures,
+                    parent: scope.parent,
+                };
+                ExprKind::Let(id, Box::new(e), Box::new(self.expr(*b, &inner)))
+            }
+            parser::ExprKind::Tuple(es) => {
+                ExprKind::Tuple(es.into_iter().map(|e| self.expr(e, scope)).collect())
+            }
+            parser::ExprKind::Record(fs) => {
+                let mut fields: Vec<(Rc<str>, Expr)> = Vec::new();
+                for (name, e) in fs {
+                    let e = self.expr(e, scope);
+                    if let Some((_, first)) = fields.iter().find(|(n, _)| **n == *name) {
+                        let err = MiniError::from(ErrorKind::DuplicateField { name })
+                            .at(e.span)
+                            .with_label(first.span, "first defined here");
+                        self.errors.push(err);
+                        continue;
+                    }
+                    fields.push((Rc::from(name), e));
+                }
+                ExprKind::Record(fields)
+            }
+            parser::ExprKind::Project(e, i) => ExprKind::Project(Box::new(self.expr(*e, scope)), i),
+            parser::ExprKind::Field(e, n) => {
+                ExprKind::Field(Box::new(self.expr(*e, scope)), Rc::from(n))
+            }
+            parser::ExprKind::Constructor(n) => match self.ctor_names.get(&n) {
+                Some(&id) => {
+                    let Constructor { name, arity, .. } = &self.ctors[id];
+                    match arity {
+                        0 => ExprKind::Construct(id, Rc::clone(name), Vec::new()),
+                        _ => {
+                            let name = Rc::clone(name);
+                            self.lift(&name, *arity, span, |args| {
+                                ExprKind::Construct(id, Rc::clone(&name), args)
+                            })
+                        }
+                    }
+                }
+                None => {
+                    self.error(ErrorKind::UndefinedConstructor { name: n }, span);
+                    ExprKind::Unit
+                }
+            },
+            parser::ExprKind::Match(e, arms) => self.matching(*e, arms, span, scope),
+        };
+        Expr { kind, span }
+    }
+
+    /// Resolve the name of the local variable, the global variable, or the function.
+    fn variable(&mut self, name: &str, scope: &Scope, span: Span) -> Option<ExprKind> {
+        scope
+            .lookup(name)
+            .map(ExprKind::Variable)
+            .or_else(|| self.global(name, span).map(ExprKind::Global))
+            .or_else(|| {
+                self.funcs
+                    .get(name)
+                    .map(|&(id, _, _)| ExprKind::Closure(id, Vec::new()))
+            })
+            .or_else(|| {
+                Builtin::from_name(name).map(|b| match b.arity() {
+                    // Constants are not functions.
+                    0 => ExprKind::Builtin(b, Vec::new()),
+                    _ => self.builtin(b, span),
+                })
+            })
+    }
+
+    /// Make the function value calling the builtin function, which is lifted like lambdas.
+    /// (located at `span`, where it is referred.)
+    fn builtin(&mut self, b: Builtin, span: Span) -> ExprKind {
+        self.lift(b.name(), b.arity(), span, |args| ExprKind::Builtin(b, args))
+    }
+
+    /// Make the function value whose body is made by `body` from the arguments.
+    fn lift<F>(&mut self, name: &str, arity: usize, span: Span, body: F) -> ExprKind
+    where
+        F: FnOnce(Vec<Expr>) -> ExprKind,
+    {
+        let id = self.funcs.len() + self.lambdas.len();
+        let args = (0..arity)
+            .map(|i| Expr {
+                kind: ExprKind::Variable(i),
+                span,
+            })
+            .collect();
+        self.lambdas.push(Function {
+            name: String::from(name),
+            arity,
+            captures: Vec::new(),
+            frame: arity,
+            body: Expr {
+                kind: body(args),
+                span,
+            },
+            span,
+        });
+        ExprKind::Closure(id, Vec::new())
+    }
+
+    /// Resolve the global variable to its last binding before the statement, like the ones
+    /// bound sequentially, or to the only binding after it. (The ambiguous uses are reported,
+    /// and resolved to the first binding.)
+    fn global(&mut self, name: &str, span: Span) -> Option<usize> {
+        let bindings = self.globals.get(name)?;
+        if let Some(b) = bindings.iter().rev().find(|b| b.position < self.position) {
+            return Some(b.id);
+        }
+        if bindings.len() > 1 {
+            let name = String::from(name);
+            let err = MiniError::from(ErrorKind::AmbiguousVariable { name }).at(span);
+            let err = bindings
+                .iter()
+                .fold(err, |err, b| err.with_label(b.span, "bound here"));
+            self.errors.push(err);
+        }
+        Some(bindings[0].id)
+    }
+
+    /// Compile `match` into the decision tree, reporting the value not covered by the arms.
+    fn matching(
+        &mut self,
+        e: parser::Expr,
+        arms: Vec<(parser::Pattern, parser::Expr)>,
+        span: Span,
+        scope: &Scope,
+    ) -> ExprKind {
+        let e = self.expr(e, scope);
+        let id = scope.new_local();
+        let clauses = arms
+            .into_iter()
+            .map(|(pat, body)| Clause {
+                span: pat.span.to(body.span),
+                pats: vec![pat],
+                guards: vec![(None, body)],
+            })
+            .collect();
+        let (decision, arms, missing) = self.clauses(clauses, vec![id], scope);
+        if let Some(mut missing) = missing {
+            let missing = missing.remove(0);
+            self.error(ErrorKind::NonExhaustive { missing }, span);
+        }
+        let kind = ExprKind::Match(Box::new(decision), arms);
+        ExprKind::Let(id, Box::new(e), Box::new(Expr { kind, span }))
+    }
+
+    /// Compile the clauses matching `vars`, returning the decision tree, the arms, and the
+    /// values not covered if exist. The clauses never matching are reported as warnings.
+    fn clauses(
+        &mut self,
+        clauses: Vec<Clause>,
+        vars: Vec<usize>,
+        scope: &Scope,
+    ) -> (Decision, Vec<Arm>, Option<Vec<String>>) {
+        let mut rows = Vec::new();
+        let mut bodies = Vec::new();
+        let mut spans = Vec::new();
+        for (i, clause) in clauses.into_iter().enumerate() {
+            let mut names = Vec::new();
+            let pats = clause
+                .pats
+                .into_iter()
+                .map(|p| self.pattern(p, &mut names))
+                .collect::<Vec<_>>();
+            for (guard, body) in clause.guards {
+                let arm = bodies.len();
+                rows.push(Row {
+                    pats: pats.clone(),
+                    arm,
+                });
+                bodies.push((i, names.clone(), guard, body));
+            }
+            spans.push(clause.span);
+        }
+
+        let mut matcher = Matcher {
+            fields: HashMap::new(),
+            path: Vec::new(),
+            bindings: vec![HashMap::new(); rows.len()],
+            guarded: bodies.iter().map(|(_, _, g, _)| g.is_some()).collect(),
+            reached: vec![false; rows.len()],
+            roots: vars.clone(),
+            missing: None,
+        };
+        let decision = self.decide(rows, &vars, &mut matcher, scope);
+        for (i, span) in spans.into_iter().enumerate() {
+            let reached = bodies
+                .iter()
+                .zip(&matcher.reached)
+                .any(|((c, _, _, _), r)| *c == i && *r);
+            if !reached {
+                let kind = ErrorKind::Unreachable;
+                self.warnings.push(Warning { kind, span });
+            }
+        }
+
+        let arms = bodies
+            .into_iter()
+            .zip(matcher.bindings)
+            .map(|((_, names, guard, body), bindings)| {
+                let mut locals = scope.locals.clone();
+                for (name, _) in names {
+                    // The arm never matches, if the variable is not bound.
+                    let id = bindings.get(&name).copied();
+                    locals.insert(name, id.unwrap_or_else(|| scope.new_local()));
+                }
+                let inner = Scope {
+                    locals,
+                    frame: scope.frame,
+                    captures: scope.captures,
+                    parent: scope.parent,
+                };
+                Arm {
+                    guard: guard.map(|g| self.expr(g, &inner)),
+                    body: self.expr(body, &inner),
+                }
+            })
+            .collect();
+        (decision, arms, matcher.missing)
+    }
+
+    /// Resolve the pattern, collecting the variables into `vars`.
+    /// (The erroneous patterns are replaced by wildcards.)
+    fn pattern(&mut self, p: parser::Pattern, vars: &mut Vec<(String, Span)>) -> Pat {
+        let span = p.span;
+        let case = match p.kind {
+            parser::PatternKind::Wildcard => return Pat::Any(None),
+            parser::PatternKind::Variable(name) => {
+                if let Some((_, first)) = vars.iter().find(|(n, _)| *n == name) {
+                    let err = MiniError::from(ErrorKind::Redefinition { name: name.clone() })
+                        .at(span)
+                        .with_label(*first, "first defined here");
+                    self.errors.push(err);
+                } else {
+                    vars.push((name.clone(), span));
+                }
+                return Pat::Any(Some(name));
+            }
+            parser::PatternKind::Value(v) => match Int::parse(&v, self.numeric.int) {
+                Some(i) => (Case::Value(i), Vec::new()),
+                None => {
+                    self.error(ErrorKind::LiteralOutOfRange { literal: v }, span);
+                    return Pat::Any(None);
+                }
+            },
+            parser::PatternKind::Str(s) => (Case::Str(Rc::from(s)), Vec::new()),
+            parser::PatternKind::Bool(b) => (Case::Bool(b), Vec::new()),
+            parser::PatternKind::Tuple(ps) => (Case::Tuple(ps.len()), ps),
+            parser::PatternKind::Constructor(name, ps) => match self.ctor_names.get(&name) {
+                Some(&id) if ps.len() == self.ctors[id].arity => (Case::Constructor(id), ps),
+                Some(_) => {
+                    self.error(ErrorKind::FieldCount { name }, span);
+                    return Pat::Any(None);
+                }
+                None => {
+                    self.error(ErrorKind::UndefinedConstructor { name }, span);
+                    return Pat::Any(None);
+                }
+            },
+        };
+        let (case, ps) = case;
+        let ps = ps.into_iter().map(|p| self.pattern(p, vars)).collect();
+        Pat::Case(case, ps)
+    }
+
+    /// Build the decision tree from the pattern matrix, where `vars` are the local variables
+    /// tested by each column.
+    fn decide(
+        &self,
+        rows: Vec<Row>,
+        vars: &[usize],
+        matcher: &mut Matcher,
+        scope: &Scope,
+    ) -> Decision {
+        let first = match rows.first() {
+            Some(row) => row,
+            None => {
+                let any = vec![String::from("_"); matcher.roots.len()];
+                matcher.missing.get_or_insert(any);
+                return Decision::Fail;
+            }
+        };
+        // The first row matches if it has no case to be tested.
+        let col = match first.pats.iter().position(|p| matches!(p, Pat::Case(..))) {
+            Some(col) => col,
+            None => {
+                let arm = first.arm;
+                for (pat, &var) in first.pats.iter().zip(vars) {
+                    matcher.bind(arm, pat, var);
+                }
+                matcher.reached[arm] = true;
+                if !matcher.guarded[arm] {
+                    return Decision::Arm(arm);
+                }
+                // The rest rows are tried if the guard is false.
+                let rest = rows[1..].to_vec();
+                let otherwise = self.decide(rest, vars, matcher, scope);
+                return Decision::Guard(arm, Box::new(otherwise));
+            }
+        };
+        let var = vars[col];
+
+        let mut cases = Vec::new();
+        for row in &rows {
+            if let Pat::Case(case, _) = &row.pats[col] {
+                if !cases.contains(case) {
+                    cases.push(case.clone());
+                }
+            }
+        }
+        let mut branches = Vec::new();
+        for case in &cases {
+            let fields = (0..self.case_arity(case))
+                .map(|i| matcher.field(var, case, i, scope))
+                .collect::<Vec<_>>();
+            let mut sub_vars = vars.to_vec();
+            sub_vars.splice(col..=col, fields.iter().copied());
+            let mut sub_rows = Vec::new();
+            for row in &rows {
+                let sub = match &row.pats[col] {
+                    Pat::Case(c, ps) if c == case => ps.clone(),
+                    Pat::Case(..) => continue,
+                    any => {
+                        matcher.bind(row.arm, any, var);
+                        vec![Pat::Any(None); fields.len()]
+                    }
+                };
+                let mut pats = row.pats.clone();
+                pats.splice(col..=col, sub);
+                sub_rows.push(Row { pats, arm: row.arm });
+            }
+            matcher.path.push((var, case.clone()));
+            let decision = self.decide(sub_rows, &sub_vars, matcher, scope);
+            matcher.path.pop();
+            branches.push((case.clone(), fields, decision));
+        }
+
+        let default = if self.is_complete(&cases) {
+            None
+        } else {
+            let mut sub_rows = Vec::new();
+            for row in &rows {
+                if let Pat::Any(_) = &row.pats[col] {
+                    matcher.bind(row.arm, &row.pats[col], var);
+                    let mut pats = row.pats.clone();
+                    pats.remove(col);
+                    sub_rows.push(Row { pats, arm: row.arm });
+                }
+            }
+            if sub_rows.is_empty() {
+                if matcher.missing.is_none() {
+                    matcher.missing = Some(self.describe(var, self.uncovered(&cases), matcher));
+                }
+                Some(Box::new(Decision::Fail))
+            } else {
+                let mut sub_vars = vars.to_vec();
+                sub_vars.remove(col);
+                Some(Box::new(self.decide(sub_rows, &sub_vars, matcher, scope)))
+            }
+        };
+        Decision::Switch(var, branches, default)
     }
 
-    /// Circulate the variable's scope depth recursively.
-    pub fn circulate(self, depth: usize) -> Self {
+    /// The number of parts bound by the case.
+    fn case_arity(&self, case: &Case) -> usize {
+        match case {
+            Case::Constructor(id) => self.ctors[*id].arity,
+            Case::Tuple(n) => *n,
+            _ => 0,
+        }
+    }
+
+    /// Whether the cases cover all values of their type.
+    fn is_complete(&self, cases: &[Case]) -> bool {
+        match cases.first() {
+            Some(Case::Constructor(id)) => self.datas[self.ctors[*id].data]
+                .ctors
+                .iter()
+                .all(|c| cases.contains(&Case::Constructor(*c))),
+            Some(Case::Bool(_)) => {
+                cases.contains(&Case::Bool(true)) && cases.contains(&Case::Bool(false))
+            }
+            Some(Case::Tuple(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// The pattern of the value not covered by the cases.
+    fn uncovered(&self, cases: &[Case]) -> String {
+        match cases.first() {
+            Some(Case::Constructor(id)) => self.datas[self.ctors[*id].data]
+                .ctors
+                .iter()
+                .find(|c| !cases.contains(&Case::Constructor(**c)))
+                .map(|c| {
+                    let case = Case::Constructor(*c);
+                    self.show_case(&case, vec![String::from("_"); self.case_arity(&case)])
+                })
+                .unwrap_or_default(),
+            Some(Case::Bool(b)) => (!b).to_string(),
+            _ => String::from("_"),
+        }
+    }
+
+    /// The patterns of the values in `matcher.roots` reaching the current node, where the part
+    /// in `var` is described by `pat`.
+    fn describe(&self, var: usize, pat: String, matcher: &Matcher) -> Vec<String> {
+        matcher
+            .roots
+            .iter()
+            .map(|&root| self.witness(root, (var, &pat), matcher))
+            .collect()
+    }
+
+    fn witness(&self, var: usize, part: (usize, &str), matcher: &Matcher) -> String {
+        if var == part.0 {
+            return String::from(part.1);
+        }
+        match matcher.path.iter().rev().find(|(v, _)| *v == var) {
+            Some((_, case)) => {
+                let fields = (0..self.case_arity(case))
+                    .map(|i| match matcher.fields.get(&(var, case.clone(), i)) {
+                        Some(&field) => self.witness(field, part, matcher),
+                        None => String::from("_"),
+                    })
+                    .collect();
+                self.show_case(case, fields)
+            }
+            None => String::from("_"),
+        }
+    }
+
+    /// Show the case as the pattern with the patterns of its parts.
+    fn show_case(&self, case: &Case, fields: Vec<String>) -> String {
+        let fields = fields.join(", ");
+        match case {
+            Case::Constructor(id) if fields.is_empty() => self.ctors[*id].name.to_string(),
+            Case::Constructor(id) => format!("{}({})", self.ctors[*id].name, fields),
+            Case::Value(i) => i.to_string(),
+            Case::Str(s) => format!("{:?}", s),
+            Case::Bool(b) => b.to_string(),
+            Case::Tuple(_) => format!("({})", fields),
+        }
+    }
+
+    /// Compile the function, returning it and the variables captured from `parent`.
+    fn function(
+        &mut self,
+        name: String,
+        args: Vec<String>,
+        body: parser::Expr,
+        span: Span,
+        parent: &Scope,
+    ) -> (Function, Vec<usize>) {
+        let arity = args.len();
+        let frame = Cell::new(arity);
+        let captures = RefCell::new(Vec::new());
+        let scope = Scope {
+            locals: args.into_iter().enumerate().map(|(i, s)| (s, i)).collect(),
+            frame: &frame,
+            captures: &captures,
+            parent: Some(parent),
+        };
+        let body = self.expr(body, &scope);
+        let (captured, captures) = captures
+            .into_inner()
+            .into_iter()
+            .map(|(_, outer, inner)| (outer, inner))
+            .unzip();
+        let func = Function {
+            name,
+            arity,
+            captures,
+            frame: frame.get(),
+            body,
+            span,
+        };
+        (func, captured)
+    }
+
+    /// Compile the function defined by clauses, which matches the arguments.
+    fn clause_function(&mut self, name: String, clauses: Vec<Clause>, parent: &Scope) -> Function {
+        let arity = clauses[0].pats.len();
+        let span = clauses[0].span.to(clauses[clauses.len() - 1].span);
+        let frame = Cell::new(arity);
+        let captures = RefCell::new(Vec::new());
+        let scope = Scope {
+            locals: HashMap::new(),
+            frame: &frame,
+            captures: &captures,
+            parent: Some(parent),
+        };
+        let (decision, arms, missing) = self.clauses(clauses, (0..arity).collect(), &scope);
+        if let Some(missing) = missing {
+            let missing = format!("{}({})", name, missing.join(", "));
+            self.error(ErrorKind::NonExhaustive { missing }, span);
+        }
+        let kind = ExprKind::Match(Box::new(decision), arms);
+        Function {
+            name,
+            arity,
+            captures: captures
+                .into_inner()
+                .into_iter()
+                .map(|(_, _, id)| id)
+                .collect(),
+            frame: frame.get(),
+            body: Expr { kind, span },
+            span,
+        }
+    }
+
+    fn error(&mut self, kind: ErrorKind, span: Span) {
+        self.errors.push(MiniError::from(kind).at(span));
+    }
+}
+
+impl Expr {
+    /// The number of local variables needed to evaluate the expression.
+    pub fn frame_size(&self) -> usize {
+        match &self.kind {
+            ExprKind::Value(_)
+            | ExprKind::Float(_)
+            | ExprKind::Str(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Unit
+            | ExprKind::Global(_) => 0,
+            ExprKind::Variable(id) => id + 1,
+            ExprKind::Operation(_, lhs, rhs) => lhs.frame_size().max(rhs.frame_size()),
+            ExprKind::Unary(_, e) => e.frame_size(),
+            ExprKind::FuncCall(_, args) | ExprKind::Builtin(_, args) | ExprKind::Tuple(args) => {
+                args.iter().map(Self::frame_size).max().unwrap_or(0)
+            }
+            ExprKind::Record(fields) => fields
+                .iter()
+                .map(|(_, e)| e.frame_size())
+                .max()
+                .unwrap_or(0),
+            ExprKind::Project(e, _) | ExprKind::Field(e, _) => e.frame_size(),
+            ExprKind::Call(f, args) => args
+                .iter()
+                .map(Self::frame_size)
+                .fold(f.frame_size(), usize::max),
+            ExprKind::Closure(_, captured) => captured.iter().map(|id| id + 1).max().unwrap_or(0),
+            ExprKind::If(c, t, f) => c.frame_size().max(t.frame_size()).max(f.frame_size()),
+            ExprKind::Let(id, e, b) => (id + 1).max(e.frame_size()).max(b.frame_size()),
+            ExprKind::Construct(_, _, args) => args.iter().map(Self::frame_size).max().unwrap_or(0),
+            ExprKind::Match(d, arms) => arms
+                .iter()
+                .map(|arm| {
+                    let guard = arm.guard.as_ref().map_or(0, Self::frame_size);
+                    guard.max(arm.body.frame_size())
+                })
+                .fold(d.frame_size(), usize::max),
+        }
+    }
+}
+
+impl Decision {
+    /// The number of local variables needed to test the value.
+    pub fn frame_size(&self) -> usize {
         match self {
-            Self::Value(v) => Self::Value(v),
-            Self::Variable(_, id) => Self::Variable(depth, id),
-            Self::Operation(op, lhs, rhs) => Self::Operation(
-                op,
-                Box::new((*lhs).circulate(depth)),
-                Box::new((*rhs).circulate(depth)),
-            ),
-            Self::FuncCall(id, args) => {
-                Self::FuncCall(id, args.into_iter().map(|e| e.circulate(depth)).collect())
+            Self::Arm(_) | Self::Fail => 0,
+            Self::Guard(_, d) => d.frame_size(),
+            Self::Switch(var, cases, default) => cases
+                .iter()
+                .map(|(_, fields, d)| {
+                    let fields = fields.iter().map(|id| id + 1).max().unwrap_or(0);
+                    fields.max(d.frame_size())
+                })
+                .fold(
+                    (var + 1).max(default.as_ref().map_or(0, |d| d.frame_size())),
+                    usize::max,
+                ),
+        }
+    }
+}
+
+impl Program {
+    /// The order to evaluate `vars` eagerly, where each variable comes after its dependencies.
+    ///
+    /// The dependencies are followed through function calls. Fails if some variables depend
+    /// on each other cyclically. The bodies of closures are not evaluated when the closures are
+    /// made, so the variables they refer to come earlier only if they don't make a cycle.
+    pub fn binding_order(&self) -> MiniResult<Vec<usize>> {
+        let func_deps = self
+            .funcs
+            .iter()
+            .map(|f| f.body.dependencies())
+            .collect::<Vec<_>>();
+        // The global variables used by `calls`, and also by `closures` if `lazy` is set.
+        let reach = |mut globals: Vec<usize>, calls: &[usize], closures: &[usize], lazy: bool| {
+            let mut visited = vec![false; self.funcs.len()];
+            let mut stack = calls.to_vec();
+            if lazy {
+                stack.extend_from_slice(closures);
+            }
+            while let Some(f) = stack.pop() {
+                if std::mem::replace(&mut visited[f], true) {
+                    continue;
+                }
+                let (globals_f, calls_f, closures_f) = &func_deps[f];
+                globals.extend_from_slice(globals_f);
+                stack.extend_from_slice(calls_f);
+                if lazy {
+                    stack.extend_from_slice(closures_f);
+                }
+            }
+            globals
+        };
+        let (deps, lazy_deps): (Vec<_>, Vec<_>) = self
+            .vars
+            .iter()
+            .map(|var| {
+                let (globals, calls, closures) = var.dependencies();
+                (
+                    reach(globals.clone(), &calls, &closures, false),
+                    reach(globals, &calls, &closures, true),
+                )
+            })
+            .unzip();
+
+        // Depth first search, where `path` is the chain of variables being visited. The groups
+        // depending on each other through closures are visited in the order of dependencies.
+        let mut order = Vec::new();
+        let mut done = vec![false; self.vars.len()];
+        let mut path = Vec::new();
+        for id in components(&lazy_deps).into_iter().flatten() {
+            self.visit(id, &deps, &mut done, &mut path, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    fn visit(
+        &self,
+        id: usize,
+        deps: &[Vec<usize>],
+        done: &mut [bool],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> MiniResult<()> {
+        if done[id] {
+            return Ok(());
+        }
+        if let Some(pos) = path.iter().position(|&v| v == id) {
+            let err = MiniError::from(ErrorKind::CyclicBinding).at(self.vars[id].span);
+            return Err(path[pos + 1..].iter().fold(err, |err, &v| {
+                err.with_label(self.vars[v].span, "part of the cycle")
+            }));
+        }
+        path.push(id);
+        for &dep in &deps[id] {
+            self.visit(dep, deps, done, path, order)?;
+        }
+        path.pop();
+        done[id] = true;
+        order.push(id);
+        Ok(())
+    }
+}
+
+impl Expr {
+    /// The global variables, the called functions, and the functions made into closures,
+    /// referred directly by the expression.
+    pub(crate) fn dependencies(&self) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+        let mut globals = Vec::new();
+        let mut calls = Vec::new();
+        let mut closures = Vec::new();
+        self.collect_dependencies(&mut globals, &mut calls, &mut closures);
+        (globals, calls, closures)
+    }
+
+    fn collect_dependencies(
+        &self,
+        globals: &mut Vec<usize>,
+        calls: &mut Vec<usize>,
+        closures: &mut Vec<usize>,
+    ) {
+        match &self.kind {
+            ExprKind::Value(_)
+            | ExprKind::Float(_)
+            | ExprKind::Str(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Unit
+            | ExprKind::Variable(_) => {}
+            ExprKind::Global(id) => globals.push(*id),
+            ExprKind::Closure(id, _) => closures.push(*id),
+            ExprKind::Operation(_, lhs, rhs) => {
+                lhs.collect_dependencies(globals, calls, closures);
+                rhs.collect_dependencies(globals, calls, closures);
+            }
+            ExprKind::Unary(_, e) => e.collect_dependencies(globals, calls, closures),
+            ExprKind::FuncCall(id, args) => {
+                calls.push(*id);
+                for arg in args {
+                    arg.collect_dependencies(globals, calls, closures);
+                }
+            }
+            ExprKind::Builtin(_, args)
+            | ExprKind::Tuple(args)
+            | ExprKind::Construct(_, _, args) => {
+                for arg in args {
+                    arg.collect_dependencies(globals, calls, closures);
+                }
+            }
+            ExprKind::Record(fields) => {
+                for (_, e) in fields {
+                    e.collect_dependencies(globals, calls, closures);
+                }
+            }
+            ExprKind::Project(e, _) | ExprKind::Field(e, _) => {
+                e.collect_dependencies(globals, calls, closures)
+            }
+            ExprKind::Call(f, args) => {
+                f.collect_dependencies(globals, calls, closures);
+                for arg in args {
+                    arg.collect_dependencies(globals, calls, closures);
+                }
+            }
+            ExprKind::If(c, t, f) => {
+                c.collect_dependencies(globals, calls, closures);
+                t.collect_dependencies(globals, calls, closures);
+                f.collect_dependencies(globals, calls, closures);
+            }
+            ExprKind::Let(_, e, b) => {
+                e.collect_dependencies(globals, calls, closures);
+                b.collect_dependencies(globals, calls, closures);
+            }
+            ExprKind::Match(_, arms) => {
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        guard.collect_dependencies(globals, calls, closures);
+                    }
+                    arm.body.collect_dependencies(globals, calls, closures);
+                }
+            }
+        }
+    }
+}
+
+/// The strongly connected components of the graph, where each component comes after the ones
+/// it depends on. (Tarjan's algorithm)
+pub(crate) fn components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State {
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+    fn visit(v: usize, edges: &[Vec<usize>], s: &mut State) {
+        s.index[v] = Some(s.next);
+        s.low[v] = s.next;
+        s.next += 1;
+        s.stack.push(v);
+        s.on_stack[v] = true;
+        for &w in &edges[v] {
+            match s.index[w] {
+                None => {
+                    visit(w, edges, s);
+                    s.low[v] = s.low[v].min(s.low[w]);
+                }
+                Some(i) if s.on_stack[w] => s.low[v] = s.low[v].min(i),
+                Some(_) => {}
             }
-            Self::If(c, t, f) => Self::If(
-                Box::new((*c).circulate(depth)),
-                Box::new((*t).circulate(depth)),
-                Box::new((*f).circulate(depth)),
-            ),
+        }
+        if Some(s.low[v]) == s.index[v] {
+            let mut component = Vec::new();
+            while let Some(w) = s.stack.pop() {
+                s.on_stack[w] = false;
+                component.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            component.reverse();
+            s.components.push(component);
+        }
+    }
+
+    let n = edges.len();
+    let mut state = State {
+        index: vec![None; n],
+        low: vec![0; n],
+        on_stack: vec![false; n],
+        stack: Vec::new(),
+        next: 0,
+        components: Vec::new(),
+    };
+    for v in 0..n {
+        if state.index[v].is_none() {
+            visit(v, edges, &mut state);
         }
     }
+    state.components
 }
 
+/// Compile the AST into `Program`, reporting all errors bundled by `MiniError::from_errors`.
 pub fn compile(ast: parser::Ast) -> MiniResult<Program> {
-    let mut vars = Vec::new();
+    compile_with(ast, Numeric::default())
+}
+
+/// Compile the AST into `Program` with the integer semantics.
+pub fn compile_with(ast: parser::Ast, numeric: Numeric) -> MiniResult<Program> {
+    compile_all(ast, numeric).map_err(MiniError::from_errors)
+}
+
+/// Compile the AST into `Program`, reporting all errors found in the AST.
+///
+/// All variables and functions are visible from anywhere in the code, regardless of the order
+/// of definitions.
+pub fn compile_all(ast: parser::Ast, numeric: Numeric) -> Result<Program, Vec<MiniError>> {
     let mut ns_vars = HashMap::new();
-    let mut funcs = Vec::new();
     let mut ns_funcs = HashMap::new();
+    let mut defined = HashMap::new();
+    let mut var_count = 0;
+    let mut ns_ctors = HashMap::new();
+    let mut ctor_spans = HashMap::new();
+    let mut data_spans = HashMap::new();
+    let mut datas = Vec::new();
+    let mut ctors = Vec::new();
+    let mut errors = Vec::new();
+    let items = merge_clauses(ast, &mut errors);
+
+    // Collect the names first, to allow forward references.
+    for (position, item) in items.iter().enumerate() {
+        let stmt = match item {
+            Item::Stmt(stmt) => stmt,
+            Item::Clauses(f, cs) => {
+                let span = cs[0].span;
+                ns_funcs.insert(f.clone(), (ns_funcs.len(), cs[0].pats.len(), span));
+                if let Some(first) = defined.insert(f.clone(), span) {
+                    errors.push(redefinition(f, span, first));
+                }
+                continue;
+            }
+        };
+        let name = match &stmt.kind {
+            parser::StmtKind::Binding(v, _) => {
+                let bindings = ns_vars.entry(v.clone()).or_insert_with(Vec::new);
+                bindings.push(Binding {
+                    position,
+                    id: var_count,
+                    span: stmt.span,
+                });
+                var_count += 1;
+                // Variables may be bound again, shadowing the earlier bindings.
+                if bindings.len() > 1 {
+                    continue;
+                }
+                v
+            }
+            parser::StmtKind::Define(f, a, _) => {
+                ns_funcs.insert(f.clone(), (ns_funcs.len(), a.len(), stmt.span));
+                f
+            }
+            // Data types and constructors have their own namespaces.
+            parser::StmtKind::Data(name, cs) => {
+                if let Some(first) = data_spans.insert(name.clone(), stmt.span) {
+                    errors.push(redefinition(name, stmt.span, first));
+                }
+                let data = datas.len();
+                let mut ids = Vec::new();
+                for (c, fields) in cs {
+                    if let Some(first) = ctor_spans.insert(c.clone(), stmt.span) {
+                        errors.push(redefinition(c, stmt.span, first));
+                    }
+                    ns_ctors.insert(c.clone(), ctors.len());
+                    ids.push(ctors.len());
+                    ctors.push(Constructor {
+                        name: Rc::from(c.as_str()),
+                        arity: fields.len(),
+                        data,
+                        fields: fields.clone(),
+                    });
+                }
+                datas.push(DataType {
+                    name: name.clone(),
+                    ctors: ids,
+                    span: stmt.span,
+                });
+                continue;
+            }
+            _ => continue,
+        };
+        if let Some(first) = defined.insert(name.clone(), stmt.span) {
+            errors.push(redefinition(name, stmt.span, first));
+        }
+    }
+
+    let mut compiler = Compiler {
+        numeric,
+        globals: &ns_vars,
+        position: 0,
+        funcs: &ns_funcs,
+        ctor_names: &ns_ctors,
+        ctors: &ctors,
+        datas: &datas,
+        lambdas: Vec::new(),
+        errors,
+        warnings: Vec::new(),
+    };
+    let mut vars = Vec::new();
+    let mut var_names = Vec::new();
+    let mut funcs = Vec::new();
     let mut prints = Vec::new();
-    for stmt in ast {
-        match stmt {
-            parser::Stmt::Binding(v, e) => {
-                let id = vars.len();
-                vars.push(Expr::from_ast(e, &ns_vars, &ns_funcs)?);
-                ns_vars.insert(v, id);
+    let frame = Cell::new(0);
+    let captures = RefCell::new(Vec::new());
+    let global = Scope {
+        locals: HashMap::new(),
+        frame: &frame,
+        captures: &captures,
+        parent: None,
+    };
+    for (position, item) in items.into_iter().enumerate() {
+        compiler.position = position;
+        let stmt = match item {
+            Item::Stmt(stmt) => stmt,
+            Item::Clauses(f, cs) => {
+                funcs.push(compiler.clause_function(f, cs, &global));
+                continue;
+            }
+        };
+        match stmt.kind {
+            parser::StmtKind::Binding(v, e) => {
+                frame.set(0);
+                vars.push(compiler.expr(e, &global));
+                var_names.push(v);
             }
-            parser::Stmt::Print(e) => {
-                prints.push(Expr::from_ast(e, &ns_vars, &ns_funcs)?);
+            parser::StmtKind::Print(e) => {
+                frame.set(0);
+                prints.push(compiler.expr(e, &global));
             }
-            parser::Stmt::Define(f, a, e) => {
-                let id = vars.len();
-                let args = a.len();
-                ns_funcs.insert(f, (id, args));
-                let local_vars = a.into_iter().enumerate().map(|(i, s)| (s, i)).collect();
-                funcs.push(Expr::from_ast(e, &local_vars, &ns_funcs)?);
+            parser::StmtKind::Define(f, a, e) => {
+                let (func, _) = compiler.function(f, a, e, stmt.span, &global);
+                funcs.push(func);
             }
+            // Already collected, or merged.
+            parser::StmtKind::Data(..) | parser::StmtKind::Clause(..) => {}
+            // Already reported by the parser.
+            parser::StmtKind::Error => {}
         }
     }
+    let defined = funcs.len();
+    funcs.append(&mut compiler.lambdas);
 
+    let (errors, warnings) = (compiler.errors, compiler.warnings);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
     Ok(Program {
         vars,
+        var_names,
         funcs,
+        defined,
         prints,
+        datas,
+        ctors,
+        warnings,
+        numeric,
     })
 }
+
+/// Merge the consecutive `def`s of the same name into clauses, if some of them have patterns
+/// or guards, or there are two or more of them.
+fn merge_clauses(ast: parser::Ast, errors: &mut Vec<MiniError>) -> Vec<Item> {
+    let mut items = Vec::new();
+    for stmt in ast {
+        let (name, clause) = match stmt.kind {
+            parser::StmtKind::Define(ref f, _, _) if !continues(&items, f) => {
+                items.push(Item::Stmt(stmt));
+                continue;
+            }
+            parser::StmtKind::Define(f, a, e) => (f, Clause::plain(a, e, stmt.span)),
+            parser::StmtKind::Clause(f, pats, guards) => {
+                let span = stmt.span;
+                (f, Clause { pats, guards, span })
+            }
+            _ => {
+                items.push(Item::Stmt(stmt));
+                continue;
+            }
+        };
+
+        // The plain definition followed by clauses is the first clause.
+        if continues(&items, &name) && matches!(items.last(), Some(Item::Stmt(_))) {
+            if let Some(Item::Stmt(prev)) = items.pop() {
+                if let parser::StmtKind::Define(_, a, e) = prev.kind {
+                    let first = Clause::plain(a, e, prev.span);
+                    items.push(Item::Clauses(name.clone(), vec![first]));
+                }
+            }
+        }
+        match items.last_mut() {
+            Some(Item::Clauses(f, cs)) if *f == name => {
+                if cs[0].pats.len() == clause.pats.len() {
+                    cs.push(clause);
+                } else {
+                    let err = MiniError::from(ErrorKind::ClauseArity { name })
+                        .at(clause.span)
+                        .with_label(cs[0].span, "first defined here");
+                    errors.push(err);
+                }
+            }
+            _ => items.push(Item::Clauses(name, vec![clause])),
+        }
+    }
+    items
+}
+
+/// Whether the last item defines the function `name`.
+fn continues(items: &[Item], name: &str) -> bool {
+    match items.last() {
+        Some(Item::Clauses(f, _)) => f == name,
+        Some(Item::Stmt(parser::Stmt {
+            kind: parser::StmtKind::Define(f, _, _),
+            ..
+        })) => f == name,
+        _ => false,
+    }
+}
+
+fn redefinition(name: &str, span: Span, first: Span) -> MiniError {
+    let name = String::from(name);
+    MiniError::from(ErrorKind::Redefinition { name })
+        .at(span)
+        .with_label(first, "first defined here")
+}
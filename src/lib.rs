@@ -5,16 +5,42 @@
 //!
 //! For the documentation of the language itself, see
 //! [README.md](https://github.com/watcol/mini-lang/blob/main/README.md).
+//!
+//! The code is executed through four stages, which are also available separately:
+//!
+//! ```
+//! use mini_lang::{compile, infer, parse, EagerEval, Evaluator, StdPrinter};
+//!
+//! let ast = parse("print 1 + 2\n")?;        // source code -> `parser::Ast`
+//! let program = compile(ast)?;              // `parser::Ast` -> `ir::Program`
+//! infer(&program)?;                         // checks the types
+//! EagerEval.evaluate(program, &mut StdPrinter)?;
+//! # Ok::<(), mini_lang::MiniError>(())
+//! ```
+mod diagnostic;
 mod error;
 mod eval;
-mod ir;
-mod parser;
+pub mod ir;
+mod number;
+pub mod parser;
 mod printer;
+mod span;
+pub mod types;
+mod value;
 
-pub use error::{MiniError, MiniResult};
+pub use diagnostic::{render, Diagnostic, Label, Severity};
+pub use error::{ErrorKind, MiniError, MiniResult, Warning};
 pub use eval::{EagerEval, Evaluator, LazyEval};
-pub use ir::{Expr, Operator, Program};
+pub use ir::{
+    compile, compile_with, Arm, Builtin, Case, Constructor, DataType, Decision, Expr, ExprKind,
+    Function, Operator, Program, UnaryOperator,
+};
+pub use number::{ArithMode, Int, IntType, Numeric, Ratio};
+pub use parser::{parse, Ast};
 pub use printer::{Printer, StdPrinter};
+pub use span::Span;
+pub use types::{infer, Row, Signature, Type};
+pub use value::{Closure, Cons, Data, Record, Tuple, Value};
 
 /// Execute the code by given evaluator and printer.
 pub fn execute<B: AsRef<str>, E: Evaluator, P: Printer>(
@@ -22,7 +48,44 @@ pub fn execute<B: AsRef<str>, E: Evaluator, P: Printer>(
     eval: &E,
     printer: &mut P,
 ) -> MiniResult<()> {
-    let ast = parser::parse(buf)?;
-    let ir = ir::compile(ast)?;
+    execute_with(buf, Numeric::default(), eval, printer)
+}
+
+/// Execute the code by given evaluator and printer, with the integer semantics.
+///
+/// The types are checked by `infer` before the evaluation, so type errors are reported without
+/// running the code.
+pub fn execute_with<B: AsRef<str>, E: Evaluator, P: Printer>(
+    buf: B,
+    numeric: Numeric,
+    eval: &E,
+    printer: &mut P,
+) -> MiniResult<()> {
+    let ast = parse(buf)?;
+    let ir = compile_with(ast, numeric)?;
+    infer(&ir)?;
+    eval.evaluate(ir, printer).map_err(MiniError::from_error)
+}
+
+/// Execute the code like `execute`, without the type check.
+pub fn execute_unchecked<B: AsRef<str>, E: Evaluator, P: Printer>(
+    buf: B,
+    eval: &E,
+    printer: &mut P,
+) -> MiniResult<()> {
+    execute_with_unchecked(buf, Numeric::default(), eval, printer)
+}
+
+/// Execute the code like `execute_with`, without the type check.
+///
+/// Type errors are found during the evaluation, and only if the evaluation reaches them.
+pub fn execute_with_unchecked<B: AsRef<str>, E: Evaluator, P: Printer>(
+    buf: B,
+    numeric: Numeric,
+    eval: &E,
+    printer: &mut P,
+) -> MiniResult<()> {
+    let ast = parse(buf)?;
+    let ir = compile_with(ast, numeric)?;
     eval.evaluate(ir, printer).map_err(MiniError::from_error)
 }
@@ -1,6 +1,10 @@
-use mini_lang::{execute, EagerEval, LazyEval, MiniError, MiniResult, StdPrinter};
+use mini_lang::{
+    compile_with, infer, parse, render, ArithMode, Diagnostic, EagerEval, Evaluator, IntType,
+    LazyEval, MiniError, MiniResult, Numeric, StdPrinter,
+};
 use std::fs::File;
-use std::io::{stdin, Read};
+use std::io::{stderr, stdin, IsTerminal, Read};
+use std::str::FromStr;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -9,34 +13,123 @@ struct Opt {
     #[structopt(short, long, help = "Enables lazy evaluation.")]
     lazy: bool,
 
+    #[structopt(long, help = "Evaluates the code without the type check.")]
+    no_check: bool,
+
+    #[structopt(
+        long,
+        default_value = "auto",
+        possible_values = &["auto", "always", "never"],
+        help = "Coloring of error messages."
+    )]
+    color: ColorChoice,
+
+    #[structopt(
+        long,
+        default_value = "i32",
+        help = "The type of integers: i32, i64, i128, or big (requires `bigint` feature)."
+    )]
+    int: IntType,
+
+    #[structopt(
+        long,
+        default_value = "checked",
+        possible_values = &["checked", "wrapping", "saturating"],
+        help = "The behavior of integer overflows."
+    )]
+    overflow: ArithMode,
+
     #[structopt(name = "FILE", help = "The input file.")]
     path: Option<String>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Prints the inferred types of the definitions, without evaluating the code.
+    Check {
+        #[structopt(name = "FILE", help = "The input file.")]
+        path: Option<String>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!("Unknown color choice: {}", s)),
+        }
+    }
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            Self::Auto => stderr().is_terminal(),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
 }
 
 fn main() {
-    inner_main().unwrap_or_else(|e| {
-        println!("{}", e);
+    let opt = Opt::from_args();
+    let mut buf = String::new();
+    inner_main(&opt, &mut buf).unwrap_or_else(|e| {
+        eprint!("{}", render(&e, &buf, opt.color.enabled()));
         std::process::exit(1);
     })
 }
 
-fn inner_main() -> MiniResult<()> {
-    let opt = Opt::from_args();
-    let mut buf = String::new();
-    match opt.path {
+fn inner_main(opt: &Opt, buf: &mut String) -> MiniResult<()> {
+    let path = match &opt.command {
+        Some(Command::Check { path }) => path,
+        None => &opt.path,
+    };
+    match path {
         Some(path) => File::open(path)
             .map_err(MiniError::from_error)?
-            .read_to_string(&mut buf)
-            .map_err(MiniError::from_error)?,
-        None => stdin()
-            .read_to_string(&mut buf)
+            .read_to_string(buf)
             .map_err(MiniError::from_error)?,
+        None => stdin().read_to_string(buf).map_err(MiniError::from_error)?,
     };
 
+    let numeric = Numeric {
+        int: opt.int,
+        mode: opt.overflow,
+    };
+    let program = compile_with(parse(&buf)?, numeric)?;
+    for warning in &program.warnings {
+        eprint!(
+            "{}",
+            Diagnostic::from(warning).render(buf, opt.color.enabled())
+        );
+    }
+    if let Some(Command::Check { .. }) = opt.command {
+        for sig in infer(&program)? {
+            println!("{}", sig);
+        }
+        return Ok(());
+    }
+    if !opt.no_check {
+        infer(&program)?;
+    }
     if opt.lazy {
-        execute(&buf, &LazyEval, &mut StdPrinter)?;
+        LazyEval.evaluate(program, &mut StdPrinter)?;
     } else {
-        execute(&buf, &EagerEval, &mut StdPrinter)?;
+        EagerEval.evaluate(program, &mut StdPrinter)?;
     }
     Ok(())
 }
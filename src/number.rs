@@ -0,0 +1,501 @@
+use crate::{ErrorKind, Operator, UnaryOperator};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+#[cfg(feature = "bigint")]
+use num_integer::Integer;
+#[cfg(feature = "bigint")]
+use num_traits::{Euclid, FromPrimitive, Pow, Signed, ToPrimitive, Zero};
+
+/// The integer semantics of the program.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Numeric {
+    /// The type of integers.
+    pub int: IntType,
+    /// The behavior when the results don't fit in `int`.
+    pub mode: ArithMode,
+}
+
+/// The types of integers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IntType {
+    #[default]
+    I32,
+    I64,
+    I128,
+    /// The arbitrary precision integer. (requires `bigint` feature)
+    #[cfg(feature = "bigint")]
+    Big,
+}
+
+impl FromStr for IntType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "i32" => Ok(Self::I32),
+            "i64" => Ok(Self::I64),
+            "i128" => Ok(Self::I128),
+            #[cfg(feature = "bigint")]
+            "big" => Ok(Self::Big),
+            _ => Err(format!("Unknown integer type: {}", s)),
+        }
+    }
+}
+
+/// The behaviors of arithmetic operations, when the results overflow.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ArithMode {
+    /// Report the error.
+    #[default]
+    Checked,
+    /// Wrap around at the boundary of the type.
+    Wrapping,
+    /// Clamp to the minimum or maximum value of the type.
+    Saturating,
+}
+
+impl FromStr for ArithMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "checked" => Ok(Self::Checked),
+            "wrapping" => Ok(Self::Wrapping),
+            "saturating" => Ok(Self::Saturating),
+            _ => Err(format!("Unknown arithmetic mode: {}", s)),
+        }
+    }
+}
+
+/// The integer value, whose type is selected by `Numeric`.
+///
+/// The operands of an operation always have the same type.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Int {
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    #[cfg(feature = "bigint")]
+    Big(BigInt),
+}
+
+impl From<i32> for Int {
+    fn from(i: i32) -> Self {
+        Self::I32(i)
+    }
+}
+
+impl fmt::Display for Int {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::I32(i) => write!(f, "{}", i),
+            Self::I64(i) => write!(f, "{}", i),
+            Self::I128(i) => write!(f, "{}", i),
+            #[cfg(feature = "bigint")]
+            Self::Big(i) => write!(f, "{}", i),
+        }
+    }
+}
+
+impl Int {
+    /// Parse the decimal literal as `ty`, fails if it is out of range.
+    pub fn parse(s: &str, ty: IntType) -> Option<Self> {
+        Some(match ty {
+            IntType::I32 => Self::I32(s.parse().ok()?),
+            IntType::I64 => Self::I64(s.parse().ok()?),
+            IntType::I128 => Self::I128(s.parse().ok()?),
+            #[cfg(feature = "bigint")]
+            IntType::Big => Self::Big(s.parse().ok()?),
+        })
+    }
+
+    /// Apply the arithmetic or bitwise operator.
+    pub(crate) fn binary(
+        op: Operator,
+        lhs: &Self,
+        rhs: &Self,
+        mode: ArithMode,
+    ) -> Result<Self, ErrorKind> {
+        Ok(match (lhs, rhs) {
+            (Self::I32(l), Self::I32(r)) => Self::I32(binary_i32(op, *l, *r, mode)?),
+            (Self::I64(l), Self::I64(r)) => Self::I64(binary_i64(op, *l, *r, mode)?),
+            (Self::I128(l), Self::I128(r)) => Self::I128(binary_i128(op, *l, *r, mode)?),
+            #[cfg(feature = "bigint")]
+            (Self::Big(l), Self::Big(r)) => Self::Big(binary_big(op, l, r)?),
+            _ => unreachable!("operands of different integer types"),
+        })
+    }
+
+    /// Apply the prefix operator.
+    pub(crate) fn unary(op: UnaryOperator, val: &Self, mode: ArithMode) -> Result<Self, ErrorKind> {
+        Ok(match val {
+            Self::I32(i) => Self::I32(unary_i32(op, *i, mode)?),
+            Self::I64(i) => Self::I64(unary_i64(op, *i, mode)?),
+            Self::I128(i) => Self::I128(unary_i128(op, *i, mode)?),
+            #[cfg(feature = "bigint")]
+            Self::Big(i) => Self::Big(match op {
+                UnaryOperator::Neg => -i,
+                _ => !i,
+            }),
+        })
+    }
+
+    /// Convert into `i128`, fails if it is out of range.
+    pub(crate) fn to_i128(&self) -> Option<i128> {
+        match self {
+            Self::I32(i) => Some(i128::from(*i)),
+            Self::I64(i) => Some(i128::from(*i)),
+            Self::I128(i) => Some(*i),
+            #[cfg(feature = "bigint")]
+            Self::Big(i) => i.to_i128(),
+        }
+    }
+
+    /// Convert from `i128` into `ty`, fails if it is out of range.
+    pub(crate) fn from_i128(i: i128, ty: IntType) -> Option<Self> {
+        Some(match ty {
+            IntType::I32 => Self::I32(i32::try_from(i).ok()?),
+            IntType::I64 => Self::I64(i64::try_from(i).ok()?),
+            IntType::I128 => Self::I128(i),
+            #[cfg(feature = "bigint")]
+            IntType::Big => Self::Big(BigInt::from(i)),
+        })
+    }
+
+    /// Convert into the nearest float.
+    pub(crate) fn to_f64(&self) -> f64 {
+        match self {
+            Self::I32(i) => f64::from(*i),
+            Self::I64(i) => *i as f64,
+            Self::I128(i) => *i as f64,
+            #[cfg(feature = "bigint")]
+            Self::Big(i) => i.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// Convert the integral float into `ty`, fails if it is out of range, or not finite.
+    pub(crate) fn from_f64(f: f64, ty: IntType) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        #[cfg(feature = "bigint")]
+        if ty == IntType::Big {
+            return BigInt::from_f64(f).map(Self::Big);
+        }
+        // 2^127, which is exactly representable.
+        let bound = 2f64.powi(127);
+        if f < -bound || f >= bound {
+            return None;
+        }
+        Self::from_i128(f as i128, ty)
+    }
+}
+
+/// The exact rational number, always kept in the lowest terms with a positive denominator.
+///
+/// Unlike integers, rationals always report overflows, regardless of `ArithMode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+impl From<i128> for Ratio {
+    fn from(i: i128) -> Self {
+        Self { num: i, den: 1 }
+    }
+}
+
+impl Ratio {
+    /// Make `num/den`, fails if `den` is zero, or it overflows.
+    pub fn new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            None
+        } else {
+            Self::reduce(num, den)
+        }
+    }
+
+    /// The numerator.
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    /// The denominator, which is always positive.
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    fn reduce(num: i128, den: i128) -> Option<Self> {
+        let g = gcd(num.unsigned_abs(), den.unsigned_abs());
+        // Only if both are 0 or `i128::MIN`.
+        let (num, den) = if g > i128::MAX as u128 {
+            (num.signum(), den.signum())
+        } else {
+            (num / g as i128, den / g as i128)
+        };
+        if den < 0 {
+            Some(Self {
+                num: num.checked_neg()?,
+                den: den.checked_neg()?,
+            })
+        } else {
+            Some(Self { num, den })
+        }
+    }
+
+    /// Apply the arithmetic operator. (`+`, `-`, `*`, `/`, `%`, and `**`)
+    pub(crate) fn binary(op: Operator, l: &Self, r: &Self) -> Result<Self, ErrorKind> {
+        let overflow = ErrorKind::Overflow { op };
+        let (a, b, c, d) = (l.num, l.den, r.num, r.den);
+        let res = match op {
+            Operator::Add | Operator::Sub => {
+                let c = if op == Operator::Sub {
+                    c.checked_neg()
+                } else {
+                    Some(c)
+                };
+                c.and_then(|c| a.checked_mul(d)?.checked_add(c.checked_mul(b)?))
+                    .and_then(|num| Self::reduce(num, b.checked_mul(d)?))
+            }
+            Operator::Mul => a
+                .checked_mul(c)
+                .and_then(|num| Self::reduce(num, b.checked_mul(d)?)),
+            Operator::Div | Operator::Rem if c == 0 => {
+                return Err(ErrorKind::DivisionByZero { op })
+            }
+            Operator::Div => a
+                .checked_mul(d)
+                .and_then(|num| Self::reduce(num, b.checked_mul(c)?)),
+            // `l - r * floor(l / r)`
+            Operator::Rem => {
+                let q = Self::binary(Operator::Div, l, r).map_err(|_| overflow.clone())?;
+                let q = Self::from(q.floor());
+                Self::binary(Operator::Mul, r, &q)
+                    .and_then(|m| Self::binary(Operator::Sub, l, &m))
+                    .ok()
+            }
+            Operator::Pow if d != 1 => {
+                return Err(ErrorKind::TypeMismatch {
+                    expected: String::from("an integer"),
+                    found: String::from("a rational"),
+                })
+            }
+            // Negative exponents take the reciprocal, which doesn't exist for 0.
+            Operator::Pow if c < 0 && a == 0 => return Err(ErrorKind::DivisionByZero { op }),
+            Operator::Pow => {
+                let (a, b) = if c < 0 { (b, a) } else { (a, b) };
+                u32::try_from(c.unsigned_abs())
+                    .ok()
+                    .and_then(|e| Self::reduce(a.checked_pow(e)?, b.checked_pow(e)?))
+            }
+            _ => unreachable!("not a rational operator"),
+        };
+        res.ok_or(overflow)
+    }
+
+    /// Compare the values, fails if it overflows.
+    pub(crate) fn checked_cmp(&self, other: &Self) -> Option<Ordering> {
+        let l = self.num.checked_mul(other.den)?;
+        let r = other.num.checked_mul(self.den)?;
+        Some(l.cmp(&r))
+    }
+
+    pub(crate) fn checked_neg(self) -> Option<Self> {
+        Some(Self {
+            num: self.num.checked_neg()?,
+            den: self.den,
+        })
+    }
+
+    /// Round toward negative infinity.
+    pub(crate) fn floor(self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    /// Round toward zero.
+    pub(crate) fn trunc(self) -> i128 {
+        self.num / self.den
+    }
+
+    /// Round to the nearest integer, and half-way cases away from zero.
+    pub(crate) fn round(self) -> i128 {
+        let (q, r) = (self.num / self.den, self.num % self.den);
+        if r.unsigned_abs() * 2 >= self.den as u128 {
+            q + self.num.signum()
+        } else {
+            q
+        }
+    }
+
+    /// Convert into the nearest float.
+    pub(crate) fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+fn is_division(op: Operator) -> bool {
+    matches!(
+        op,
+        Operator::Div
+            | Operator::Rem
+            | Operator::TruncDiv
+            | Operator::TruncRem
+            | Operator::EuclidDiv
+            | Operator::EuclidRem
+    )
+}
+
+macro_rules! primitive {
+    ($binary:ident, $unary:ident, $t:ty) => {
+        fn $binary(op: Operator, l: $t, r: $t, mode: ArithMode) -> Result<$t, ErrorKind> {
+            use ArithMode::*;
+            if is_division(op) && r == 0 {
+                return Err(ErrorKind::DivisionByZero { op });
+            }
+            if matches!(op, Operator::Pow) && r < 0 {
+                return Err(ErrorKind::NegativeExponent);
+            }
+            // Floored results are adjusted from truncated ones, if the signs differ.
+            let floor = |q: $t| {
+                if l.wrapping_rem(r) != 0 && (l < 0) != (r < 0) {
+                    q - 1
+                } else {
+                    q
+                }
+            };
+            let modulo = |m: $t| {
+                if m != 0 && (m < 0) != (r < 0) {
+                    m + r
+                } else {
+                    m
+                }
+            };
+            // The exponent, or the shift amount.
+            let amount = u32::try_from(r).ok();
+            let res = match (op, mode) {
+                (Operator::Add, Checked) => l.checked_add(r),
+                (Operator::Add, Wrapping) => Some(l.wrapping_add(r)),
+                (Operator::Add, Saturating) => Some(l.saturating_add(r)),
+                (Operator::Sub, Checked) => l.checked_sub(r),
+                (Operator::Sub, Wrapping) => Some(l.wrapping_sub(r)),
+                (Operator::Sub, Saturating) => Some(l.saturating_sub(r)),
+                (Operator::Mul, Checked) => l.checked_mul(r),
+                (Operator::Mul, Wrapping) => Some(l.wrapping_mul(r)),
+                (Operator::Mul, Saturating) => Some(l.saturating_mul(r)),
+                (Operator::Div, Checked) => l.checked_div(r).map(floor),
+                (Operator::Div, Wrapping) => Some(floor(l.wrapping_div(r))),
+                (Operator::Div, Saturating) => Some(floor(l.saturating_div(r))),
+                (Operator::Rem, Checked) => l.checked_rem(r).map(modulo),
+                (Operator::Rem, _) => Some(modulo(l.wrapping_rem(r))),
+                (Operator::TruncDiv, Checked) => l.checked_div(r),
+                (Operator::TruncDiv, Wrapping) => Some(l.wrapping_div(r)),
+                (Operator::TruncDiv, Saturating) => Some(l.saturating_div(r)),
+                (Operator::TruncRem, Checked) => l.checked_rem(r),
+                (Operator::TruncRem, _) => Some(l.wrapping_rem(r)),
+                (Operator::EuclidDiv, Checked) => l.checked_div_euclid(r),
+                (Operator::EuclidDiv, Wrapping) => Some(l.wrapping_div_euclid(r)),
+                (Operator::EuclidDiv, Saturating) => {
+                    Some(l.checked_div_euclid(r).unwrap_or(<$t>::MAX))
+                }
+                (Operator::EuclidRem, Checked) => l.checked_rem_euclid(r),
+                (Operator::EuclidRem, _) => Some(l.wrapping_rem_euclid(r)),
+                (Operator::Pow, Checked) => amount.and_then(|e| l.checked_pow(e)),
+                (Operator::Pow, Wrapping) => amount.map(|e| l.wrapping_pow(e)),
+                (Operator::Pow, Saturating) => amount.map(|e| l.saturating_pow(e)),
+                // Fails if any bits are shifted out.
+                (Operator::Shl, Checked) => {
+                    amount.and_then(|n| l.checked_shl(n).filter(|v| v >> n == l))
+                }
+                (Operator::Shl, Wrapping) => amount.map(|n| l.checked_shl(n).unwrap_or(0)),
+                (Operator::Shl, Saturating) => {
+                    amount.map(|n| match l.checked_shl(n).filter(|v| v >> n == l) {
+                        Some(v) => v,
+                        None if l == 0 => 0,
+                        None if l < 0 => <$t>::MIN,
+                        None => <$t>::MAX,
+                    })
+                }
+                (Operator::Shr, Checked) => amount.and_then(|n| l.checked_shr(n)),
+                (Operator::Shr, _) => {
+                    amount.map(|n| l.checked_shr(n).unwrap_or(if l < 0 { -1 } else { 0 }))
+                }
+                (Operator::BitAnd, _) => Some(l & r),
+                (Operator::BitOr, _) => Some(l | r),
+                (Operator::BitXor, _) => Some(l ^ r),
+                _ => unreachable!("not an arithmetic operator"),
+            };
+            res.ok_or(ErrorKind::Overflow { op })
+        }
+
+        fn $unary(op: UnaryOperator, i: $t, mode: ArithMode) -> Result<$t, ErrorKind> {
+            match (op, mode) {
+                (UnaryOperator::Neg, ArithMode::Checked) => {
+                    i.checked_neg().ok_or(ErrorKind::UnaryOverflow { op })
+                }
+                (UnaryOperator::Neg, ArithMode::Wrapping) => Ok(i.wrapping_neg()),
+                (UnaryOperator::Neg, ArithMode::Saturating) => Ok(i.saturating_neg()),
+                _ => Ok(!i),
+            }
+        }
+    };
+}
+
+primitive!(binary_i32, unary_i32, i32);
+primitive!(binary_i64, unary_i64, i64);
+primitive!(binary_i128, unary_i128, i128);
+
+/// The arbitrary precision integers never overflow, except the exponents and the shift amounts.
+#[cfg(feature = "bigint")]
+fn binary_big(op: Operator, l: &BigInt, r: &BigInt) -> Result<BigInt, ErrorKind> {
+    if is_division(op) && r.is_zero() {
+        return Err(ErrorKind::DivisionByZero { op });
+    }
+    let overflow = ErrorKind::Overflow { op };
+    Ok(match op {
+        Operator::Add => l + r,
+        Operator::Sub => l - r,
+        Operator::Mul => l * r,
+        Operator::Div => l.div_floor(r),
+        Operator::Rem => l.mod_floor(r),
+        Operator::TruncDiv => l / r,
+        Operator::TruncRem => l % r,
+        Operator::EuclidDiv => Euclid::div_euclid(l, r),
+        Operator::EuclidRem => Euclid::rem_euclid(l, r),
+        Operator::Pow if r.is_negative() => return Err(ErrorKind::NegativeExponent),
+        Operator::Pow => Pow::pow(l, r.to_u32().ok_or(overflow)?),
+        Operator::Shl if r.is_negative() => return Err(overflow),
+        Operator::Shl => l << r.to_usize().ok_or(overflow)?,
+        Operator::Shr if r.is_negative() => return Err(overflow),
+        // Too large shift amounts shift out all bits.
+        Operator::Shr => match r.to_usize() {
+            Some(n) => l >> n,
+            None if l.is_negative() => BigInt::from(-1),
+            None => BigInt::zero(),
+        },
+        Operator::BitAnd => l & r,
+        Operator::BitOr => l | r,
+        Operator::BitXor => l ^ r,
+        _ => unreachable!("not an arithmetic operator"),
+    })
+}
@@ -1,25 +1,175 @@
+//! The syntax tree, and the parser to build it.
+use crate::span::{Lines, Span};
 use crate::{MiniError, MiniResult};
+use peg::error::ParseError;
+use peg::str::LineCol;
 
+/// The syntax tree of the whole code, which is a list of statements.
 pub type Ast = Vec<Stmt>;
 
+/// The statement, located by the span in the source code.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Stmt {
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+/// The kinds of statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StmtKind {
+    /// `let <name> = <expr>`
     Binding(String, Expr),
+    /// `print <expr>`
     Print(Expr),
+    /// `def <name>(<args>) = <expr>`
     Define(String, Vec<String>, Expr),
+    /// `def <name>(<patterns>) = <expr>`, or `def <name>(<patterns>) | <guard> = <expr> | ...`
+    /// with guards, where `else` is `None`. Consecutive `def`s of the same name are the clauses
+    /// of one function, tried in order. (`def` only with variables and no guard is `Define`.)
+    Clause(String, Vec<Pattern>, Vec<(Option<Expr>, Expr)>),
+    /// `data <name> = <constructor>(<field types>) | ...`, where constructors without fields
+    /// omit the parentheses.
+    Data(String, Vec<(String, Vec<TypeExpr>)>),
+    /// The malformed statement, skipped by the recovering parser.
+    Error,
+}
+
+/// The expression, located by the span in the source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
 }
 
+/// The kinds of expression.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Expr {
-    Value(i32),
+#[non_exhaustive]
+pub enum ExprKind {
+    /// The number literal, in decimal digits with an optional `-` sign.
+    Value(String),
+    /// The float literal, with the decimal point or the exponent. (e.g. `3.14`, `-1e-3`)
+    Float(String),
+    /// The string literal, whose escape sequences are already resolved.
+    Str(String),
+    /// `true`, or `false`
+    Bool(bool),
+    /// `()`
+    Unit,
+    /// The variable's name.
     Variable(String),
+    /// The operator, and left side value, and right side value.
     Operation(Operator, Box<Expr>, Box<Expr>),
+    /// The prefix operator, and the operand.
+    Unary(UnaryOperator, Box<Expr>),
+    /// The function name, and list of arguments.
     FuncCall(String, Vec<Expr>),
+    /// The expression to be called, and list of arguments. (e.g. `f(1)(2)`)
+    Call(Box<Expr>, Vec<Expr>),
+    /// `\<arg> -> <expr>`, or `\(<args>) -> <expr>`
+    Lambda(Vec<String>, Box<Expr>),
+    /// `(<expr>, <expr>, ...)`, with two or more elements.
+    Tuple(Vec<Expr>),
+    /// `{<name>: <expr>, ...}`
+    Record(Vec<(String, Expr)>),
+    /// `<expr>.<index>`, the element of the tuple. (0-origin)
+    Project(Box<Expr>, usize),
+    /// `<expr>.<name>`, the field of the record.
+    Field(Box<Expr>, String),
+    /// The condition, the expression evaluated if condition is true, and false.
     If(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// `let <name> = <expr> in <expr>`, or `where` clauses of `def`.
+    Let(String, Box<Expr>, Box<Expr>),
+    /// The constructor's name, which starts with an uppercase letter.
+    Constructor(String),
+    /// `match <expr> { <pattern> => <expr>, ... }`, where arms are separated by commas or
+    /// newlines.
+    Match(Box<Expr>, Vec<(Pattern, Expr)>),
+}
+
+/// The pattern of `match`, located by the span in the source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+/// The kinds of pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatternKind {
+    /// `_`, which matches anything.
+    Wildcard,
+    /// The variable's name, which matches anything and binds it.
+    Variable(String),
+    /// The integer literal, with an optional `-` sign.
+    Value(String),
+    /// The string literal.
+    Str(String),
+    /// `true`, or `false`
+    Bool(bool),
+    /// `(<pattern>, <pattern>, ...)`, with two or more elements.
+    Tuple(Vec<Pattern>),
+    /// The constructor's name, and the patterns of its fields.
+    Constructor(String, Vec<Pattern>),
+}
+
+/// The type of the constructor's field, located by the span in the source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeExpr {
+    pub kind: TypeExprKind,
+    pub span: Span,
+}
+
+/// The kinds of type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TypeExprKind {
+    /// The type variable, which is the parameter of the data type. (e.g. `a`)
+    Variable(String),
+    /// The type's name, and its arguments. (e.g. `Int`, `Tree a`)
+    Named(String, Vec<TypeExpr>),
+    /// `()`
+    Unit,
+    /// `[<type>]`
+    List(Box<TypeExpr>),
+    /// `(<type>, <type>, ...)`, with two or more elements.
+    Tuple(Vec<TypeExpr>),
+    /// `<type> -> <type>`
+    Function(Box<TypeExpr>, Box<TypeExpr>),
+}
+
+/// The suffixes of the atom.
+enum Postfix {
+    Call(Vec<Expr>),
+    Project(usize),
+    Field(String),
+}
+
+impl Expr {
+    fn operation(op: Operator, lhs: Self, rhs: Self) -> Self {
+        let span = lhs.span.to(rhs.span);
+        Self {
+            kind: ExprKind::Operation(op, Box::new(lhs), Box::new(rhs)),
+            span,
+        }
+    }
+
+    /// Put the expression inside the bindings of `where`.
+    fn with_where(self, bindings: Vec<(String, Self, Span)>) -> Self {
+        bindings.into_iter().rev().fold(self, |body, (v, e, span)| {
+            let span = span.to(body.span);
+            Self {
+                kind: ExprKind::Let(v, Box::new(e), Box::new(body)),
+                span,
+            }
+        })
+    }
 }
 
 /// The operator enum for expressions.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Operator {
     /// `+`
     Add,
@@ -27,10 +177,18 @@ pub enum Operator {
     Sub,
     /// `*`
     Mul,
-    /// `/`
+    /// `/` (floored)
     Div,
-    /// `%`
+    /// `%` (floored, which has the same sign as the right side)
     Rem,
+    /// `quot` (truncated toward zero)
+    TruncDiv,
+    /// `rem` (truncated, which has the same sign as the left side)
+    TruncRem,
+    /// `div` (Euclidean)
+    EuclidDiv,
+    /// `mod` (Euclidean, which is never negative)
+    EuclidRem,
     /// `>`
     Gt,
     /// `>=`, or `=>`
@@ -43,56 +201,280 @@ pub enum Operator {
     Eq,
     /// `!=`
     Neq,
+    /// `**`
+    Pow,
+    /// `&`
+    BitAnd,
+    /// `|`
+    BitOr,
+    /// `^`
+    BitXor,
+    /// `<<`
+    Shl,
+    /// `>>`
+    Shr,
+    /// `++`, concatenates strings.
+    Concat,
+    /// `&&` (the right side is evaluated only if the left side is `true`.)
+    And,
+    /// `||` (the right side is evaluated only if the left side is `false`.)
+    Or,
 }
 
+/// The prefix operator enum for expressions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnaryOperator {
+    /// `-`
+    Neg,
+    /// `!`
+    Not,
+    /// `~`
+    BitNot,
+}
+
+/// Parse the code, reporting all syntax errors if exist.
 pub fn parse<S: AsRef<str>>(input: S) -> MiniResult<Ast> {
-    parser::program(input.as_ref()).map_err(MiniError::Parse)
+    let (ast, errors) = parse_recovering(input);
+    if errors.is_empty() {
+        Ok(ast)
+    } else {
+        Err(MiniError::from_errors(errors))
+    }
 }
 
-peg::parser! { grammar parser() for str {
-    rule _ = ("\t"/" "/"\\\n"/"\\\r")*
-    rule __ = (_ ("\n"/"\r"))*
-    rule space() = ("\t"/" "/"\\\n"/"\\\r")+
+/// Parse the code, skipping malformed statements until the next statement boundary.
+///
+/// The skipped statements are left in the AST as `StmtKind::Error`, and all syntax errors are
+/// returned with it.
+pub fn parse_recovering<S: AsRef<str>>(input: S) -> (Ast, Vec<MiniError>) {
+    let input = input.as_ref();
+    let lines = Lines::new(input);
+    let mut ast = Vec::new();
+    let mut errors = Vec::new();
+    let mut pos = 0;
+    loop {
+        let rest = &input[pos..];
+        let offset = lines.offset(pos);
+        let (stmts, stop) = match parser::prefix(rest, &offset) {
+            Ok(res) => res,
+            Err(_) => unreachable!("`prefix` accepts any input"),
+        };
+        ast.extend(stmts);
+        let e = match parser::program(&rest[stop..], &offset.offset(pos + stop)) {
+            Ok(stmts) => {
+                ast.extend(stmts);
+                break;
+            }
+            Err(e) => e,
+        };
+
+        let start = pos + stop;
+        let failed = start + e.location.offset;
+        let span = lines.span(failed, failed);
+        errors.push(MiniError::Parse(ParseError {
+            location: LineCol {
+                line: span.line,
+                column: span.column,
+                offset: failed,
+            },
+            expected: e.expected,
+        }));
+
+        let next = next_boundary(input, start, failed);
+        let skipped = &input[start..next];
+        let leading = skipped.len() - skipped.trim_start().len();
+        let trailing = skipped.len() - skipped.trim_end().len();
+        ast.push(Stmt {
+            kind: StmtKind::Error,
+            span: lines.span(start + leading, next - trailing),
+        });
+        if next >= input.len() {
+            break;
+        }
+        pos = next;
+    }
+    (ast, errors)
+}
+
+/// Find the start of the first line after `start`, which begins with a statement keyword.
+/// (The search begins with the line where the error occured.)
+fn next_boundary(input: &str, start: usize, failed: usize) -> usize {
+    let mut offset = input[..failed].rfind('\n').map_or(0, |i| i + 1);
+    loop {
+        let line = input[offset..].trim_start_matches(&[' ', '\t'][..]);
+        let starts_stmt = ["print", "let", "def", "data"].iter().any(|k| {
+            line.starts_with(k)
+                && !line[k.len()..].starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        });
+        if offset > start && starts_stmt {
+            return offset;
+        }
+        match input[offset..].find('\n') {
+            Some(i) => offset += i + 1,
+            None => return input.len(),
+        }
+    }
+}
+
+peg::parser! { grammar parser(lines: &Lines) for str {
+    rule _ = blank()*
+    rule __ = (_ newline())*
+    rule space() = blank()+
+
+    // Whitespaces which may contain newlines, used where the statement can't end.
+    rule ws() = (blank() / newline())*
+    rule wspace() = (blank() / newline())+
+
+    // Whitespaces before a binary operator, containing newlines only if `nl`.
+    rule sep(nl: bool) = multiline(nl) ws() / _
+
+    rule multiline(nl: bool) = {? if nl { Ok(()) } else { Err("multiline") } }
+
+    rule blank()
+        = "\t" / " "
+        / "\\" ("\t"/" ")* line_comment()? newline()
+        / line_comment()
+        / block_comment()
+
+    rule newline() = "\r\n" / "\n" / "\r"
+
+    rule line_comment() = "//" (!['\n' | '\r'] [_])*
+
+    rule block_comment() = "/*" (block_comment() / !"*/" [_])* "*/"
 
     pub rule program() -> Ast
-        = stmt()*
+        = __ s:stmt()* _ { s }
+
+    // Statements as many as possible, and the position where the parsing stopped.
+    pub rule prefix() -> (Ast, usize)
+        = __ s:stmt()* p:position!() [_]* { (s, p) }
 
     rule stmt() -> Stmt
         = print()
         / binding()
         / define()
+        / data()
 
     rule print() -> Stmt
-        = _ "print" space() e:expr() __ { Stmt::Print(e) }
+        = _ s:position!() "print" wspace() e:expr(false) t:position!() __ {
+            Stmt { kind: StmtKind::Print(e), span: lines.span(s, t) }
+        }
 
     rule binding() -> Stmt
-        = _ "let" space() v:ident() _ "=" _ e:expr() __ { Stmt::Binding(v, e) }
+        = _ s:position!() "let" wspace() v:ident() ws() "=" ws() e:expr(false) t:position!() __ {
+            Stmt { kind: StmtKind::Binding(v, e), span: lines.span(s, t) }
+        }
 
     rule define() -> Stmt
-        = _ "def" space() n:ident() _
-          "(" a:((_ a:ident() _ { a }) ** (",")) ","? _ ")" _
-          "=" _ e:expr() __ { Stmt::Define(n, a, e) }
+        = _ s:position!() "def" wspace() n:ident() ws()
+          a:params_list() ws() "=" ws() e:expr(false) w:where_clause()? t:position!() __ {
+            let e = e.with_where(w.unwrap_or_default());
+            Stmt { kind: StmtKind::Define(n, a, e), span: lines.span(s, t) }
+        }
+        / _ s:position!() "def" wspace() n:ident() ws()
+          ps:patterns() ws() gs:clause_body() w:where_clause()? t:position!() __ {
+            // The bindings of `where` are visible from all guards.
+            let w = w.unwrap_or_default();
+            let gs = gs
+                .into_iter()
+                .map(|(g, e)| (g.map(|g| g.with_where(w.clone())), e.with_where(w.clone())))
+                .collect();
+            Stmt { kind: StmtKind::Clause(n, ps, gs), span: lines.span(s, t) }
+        }
+
+    rule patterns() -> Vec<Pattern>
+        = "(" ps:((ws() p:pattern() ws() { p }) ** ",") ","? ws() ")" { ps }
+
+    rule clause_body() -> Vec<(Option<Expr>, Expr)>
+        = "=" ws() e:expr(false) { vec![(None, e)] }
+        / guard() ++ ws()
+
+    // Newlines are allowed before `=`, which can't start a statement.
+    rule guard() -> (Option<Expr>, Expr)
+        = "|" ws() g:guard_cond() ws() "=" ws() e:expr(false) { (g, e) }
+
+    rule guard_cond() -> Option<Expr>
+        = "else" !['a'..='z' | '_'] { None }
+        / e:expr(false) { Some(e) }
+
+    // Whether `|` starts the next guard, instead of the operator.
+    rule guard_ahead() = _ guard_cond() _ "=" !['=' | '>' | '<']
+
+    rule data() -> Stmt
+        = _ s:position!() "data" wspace() n:cname() ws() "=" ws()
+          cs:(constructor() ++ (ws() "|" ws())) t:position!() __ {
+            Stmt { kind: StmtKind::Data(n, cs), span: lines.span(s, t) }
+        }
+
+    rule constructor() -> (String, Vec<TypeExpr>)
+        = n:cname() _ "(" ts:((ws() t:type_expr() ws() { t }) ** ",") ","? ws() ")" { (n, ts) }
+        / n:cname() { (n, Vec::new()) }
+
+    rule type_expr() -> TypeExpr
+        = s:position!() a:type_app() _ "->" ws() b:type_expr() t:position!() {
+            let kind = TypeExprKind::Function(Box::new(a), Box::new(b));
+            TypeExpr { kind, span: lines.span(s, t) }
+        }
+        / type_app()
 
-    rule expr() -> Expr = eq()
+    rule type_app() -> TypeExpr
+        = s:position!() n:cname() a:(_ a:type_atom() { a })+ t:position!() {
+            TypeExpr { kind: TypeExprKind::Named(n, a), span: lines.span(s, t) }
+        }
+        / type_atom()
+
+    rule type_atom() -> TypeExpr
+        = s:position!() k:type_atom_kind() t:position!() { TypeExpr { kind: k, span: lines.span(s, t) } }
+
+    rule type_atom_kind() -> TypeExprKind
+        = n:cname() { TypeExprKind::Named(n, Vec::new()) }
+        / n:ident() { TypeExprKind::Variable(n) }
+        / "(" ws() ")" { TypeExprKind::Unit }
+        / "(" ws() t:type_expr() ws() ")" { t.kind }
+        / "(" ws() ts:(type_expr() **<2,> (ws() "," ws())) ws() ")" { TypeExprKind::Tuple(ts) }
+        / "[" ws() t:type_expr() ws() "]" { TypeExprKind::List(Box::new(t)) }
+
+    // `where` can't start a statement, so newlines are allowed before it.
+    rule where_clause() -> Vec<(String, Expr, Span)>
+        = wspace() "where" wspace() bs:(where_binding() ++ (ws() "," ws())) { bs }
+
+    rule where_binding() -> (String, Expr, Span)
+        = s:position!() v:ident() ws() "=" ws() e:expr(false) t:position!() {
+            (v, e, lines.span(s, t))
+        }
+
+    rule expr(nl: bool) -> Expr = or(nl)
+
+    rule or(nl: bool) -> Expr
+        = l:and(nl) rs:( sep(nl) "||" ws() r:and(nl) { r })* {
+            rs.into_iter().fold(l, |l, r| Expr::operation(Operator::Or, l, r))
+        }
+
+    rule and(nl: bool) -> Expr
+        = l:eq(nl) rs:( sep(nl) "&&" ws() r:eq(nl) { r })* {
+            rs.into_iter().fold(l, |l, r| Expr::operation(Operator::And, l, r))
+        }
 
-    rule eq() -> Expr
-        = l:comp() rs:( _ op:$(("=="/"!=")) _ r:comp() { (op, r) })*
+    rule eq(nl: bool) -> Expr
+        = l:comp(nl) rs:( sep(nl) op:$(("=="/"!=")) ws() r:comp(nl) { (op, r) })*
         {
-            rs.into_iter().fold(l, |l, (op, r)| Expr::Operation(
+            rs.into_iter().fold(l, |l, (op, r)| Expr::operation(
                 match op {
                     "==" => Operator::Eq,
                     "!=" => Operator::Neq,
                     _ => unreachable!(),
                 },
-                Box::new(l),
-                Box::new(r),
+                l,
+                r,
             ))
         }
 
-    rule comp() -> Expr
-        = l:add() rs:( _ op:$(("=>"/"=<"/">="/"<="/">"/"<")) _ r:add() { (op, r) })*
+    rule comp(nl: bool) -> Expr
+        = l:concat(nl)
+          rs:( sep(nl) op:$(("=>"/"=<"/">="/"<="/">"/"<")) ws() r:concat(nl) { (op, r) })*
         {
-            rs.into_iter().fold(l, |l, (op, r)| Expr::Operation(
+            rs.into_iter().fold(l, |l, (op, r)| Expr::operation(
                 match op {
                     ">" => Operator::Gt,
                     "=>" | ">=" => Operator::Ge,
@@ -100,65 +482,270 @@ peg::parser! { grammar parser() for str {
                     "=<" | "<=" => Operator::Le,
                     _ => unreachable!(),
                 },
-                Box::new(l),
-                Box::new(r),
+                l,
+                r,
             ))
         }
 
-    rule add() -> Expr
-        = l:mul() rs:( _ op:$(("+"/"-")) _ r:mul() { (op, r) })*
+    rule concat(nl: bool) -> Expr
+        = l:bitor(nl) rs:( sep(nl) "++" ws() r:bitor(nl) { r })* {
+            rs.into_iter().fold(l, |l, r| Expr::operation(Operator::Concat, l, r))
+        }
+
+    rule bitor(nl: bool) -> Expr
+        = l:bitxor(nl) rs:( sep(nl) "|" !"|" !guard_ahead() ws() r:bitxor(nl) { r })* {
+            rs.into_iter().fold(l, |l, r| Expr::operation(Operator::BitOr, l, r))
+        }
+
+    rule bitxor(nl: bool) -> Expr
+        = l:bitand(nl) rs:( sep(nl) "^" ws() r:bitand(nl) { r })* {
+            rs.into_iter().fold(l, |l, r| Expr::operation(Operator::BitXor, l, r))
+        }
+
+    rule bitand(nl: bool) -> Expr
+        = l:shift(nl) rs:( sep(nl) "&" !"&" ws() r:shift(nl) { r })* {
+            rs.into_iter().fold(l, |l, r| Expr::operation(Operator::BitAnd, l, r))
+        }
+
+    rule shift(nl: bool) -> Expr
+        = l:add(nl) rs:( sep(nl) op:$(("<<"/">>")) ws() r:add(nl) { (op, r) })*
         {
-            rs.into_iter().fold(l, |l, (op, r)| Expr::Operation(
+            rs.into_iter().fold(l, |l, (op, r)| Expr::operation(
+                match op {
+                    "<<" => Operator::Shl,
+                    ">>" => Operator::Shr,
+                    _ => unreachable!(),
+                },
+                l,
+                r,
+            ))
+        }
+
+    rule add(nl: bool) -> Expr
+        = l:mul(nl) rs:( sep(nl) op:$(("+" !"+" / "-")) ws() r:mul(nl) { (op, r) })*
+        {
+            rs.into_iter().fold(l, |l, (op, r)| Expr::operation(
                 match op {
                     "+" => Operator::Add,
                     "-" => Operator::Sub,
                     _ => unreachable!(),
                 },
-                Box::new(l),
-                Box::new(r),
+                l,
+                r,
             ))
         }
 
-    rule mul() -> Expr
-        = l:atom() rs:( _ op:$(("*"/"/"/"%")) _ r:atom() { (op, r) })*
+    rule mul(nl: bool) -> Expr
+        = l:unary(nl) rs:( sep(nl) op:mul_op() ws() r:unary(nl) { (op, r) })*
         {
-            rs.into_iter().fold(l, |l, (op, r)| Expr::Operation(
+            rs.into_iter().fold(l, |l, (op, r)| Expr::operation(
                 match op {
                     "*" => Operator::Mul,
                     "/" => Operator::Div,
                     "%" => Operator::Rem,
+                    "quot" => Operator::TruncDiv,
+                    "rem" => Operator::TruncRem,
+                    "div" => Operator::EuclidDiv,
+                    "mod" => Operator::EuclidRem,
                     _ => unreachable!(),
                 },
-                Box::new(l),
-                Box::new(r),
+                l,
+                r,
             ))
         }
 
-    rule atom() -> Expr
-        = n:number() { Expr::Value(n) }
-        / "(" _ e:expr() _ ")" { e }
+    rule mul_op() -> &'input str
+        = $("*" / "/" / "%")
+        / op:$("quot" / "rem" / "div" / "mod") !['a'..='z' | '_'] { op }
+
+    // Negated bare number literals are folded into the literals, so `-2147483648` fits in `i32`.
+    rule unary(nl: bool) -> Expr
+        = s:position!() op:$("-" / "!" / "~") _ lit:(&(float() / number()))? e:unary(nl) {
+            let span = lines.span(s, s).to(e.span);
+            let negate = |n: String| match n.strip_prefix('-') {
+                Some(n) => String::from(n),
+                None => format!("-{}", n),
+            };
+            let kind = match (op, e.kind) {
+                ("-", ExprKind::Value(n)) if lit.is_some() => ExprKind::Value(negate(n)),
+                ("-", ExprKind::Float(n)) if lit.is_some() => ExprKind::Float(negate(n)),
+                (op, kind) => ExprKind::Unary(
+                    match op {
+                        "-" => UnaryOperator::Neg,
+                        "!" => UnaryOperator::Not,
+                        "~" => UnaryOperator::BitNot,
+                        _ => unreachable!(),
+                    },
+                    Box::new(Expr { kind, span: e.span }),
+                ),
+            };
+            Expr { kind, span }
+        }
+        / power(nl)
+
+    // Right associative, and binds tighter than prefix operators on the left. (`-2 ** 2 == -4`)
+    rule power(nl: bool) -> Expr
+        = l:atom(nl) r:( sep(nl) "**" ws() r:unary(nl) { r })? {
+            match r {
+                Some(r) => Expr::operation(Operator::Pow, l, r),
+                None => l,
+            }
+        }
+
+    rule atom(nl: bool) -> Expr
+        = s:position!() f:primary(nl) ps:(p:postfix() t:position!() { (p, t) })* {
+            ps.into_iter().fold(f, |f, (p, t)| {
+                let kind = match p {
+                    Postfix::Call(a) => ExprKind::Call(Box::new(f), a),
+                    Postfix::Project(i) => ExprKind::Project(Box::new(f), i),
+                    Postfix::Field(n) => ExprKind::Field(Box::new(f), n),
+                };
+                Expr { kind, span: lines.span(s, t) }
+            })
+        }
+
+    rule postfix() -> Postfix
+        = _ a:args() { Postfix::Call(a) }
+        / "." i:$(['0'..='9']+) {? i.parse().map(Postfix::Project).or(Err("an index")) }
+        / "." n:ident() { Postfix::Field(n) }
+
+    rule primary(nl: bool) -> Expr
+        = s:position!() "(" ws() ")" t:position!() {
+            Expr { kind: ExprKind::Unit, span: lines.span(s, t) }
+        }
+        / s:position!() "(" ws() e:expr(true) ws() ")" t:position!() {
+            Expr { span: lines.span(s, t), ..e }
+        }
+        / s:position!() "(" es:((ws() e:expr(true) ws() { e }) **<2,> ",") ","? ws() ")"
+          t:position!() {
+            Expr { kind: ExprKind::Tuple(es), span: lines.span(s, t) }
+        }
+        / s:position!() "{" fs:((ws() f:field() ws() { f }) ** ",") ","? ws() "}" t:position!() {
+            Expr { kind: ExprKind::Record(fs), span: lines.span(s, t) }
+        }
+        / s:position!() k:atom_kind(nl) t:position!() { Expr { kind: k, span: lines.span(s, t) } }
+
+    rule atom_kind(nl: bool) -> ExprKind
+        = s:string() { ExprKind::Str(s) }
+        / n:float() { ExprKind::Float(n) }
+        / n:number() { ExprKind::Value(n) }
+        / b:boolean() { ExprKind::Bool(b) }
+        / match_expr()
         / funccall()
-        / if_expr()
-        / v:ident() { Expr::Variable(v) }
+        / if_expr(nl)
+        / let_expr(nl)
+        / lambda(nl)
+        / n:cname() { ExprKind::Constructor(n) }
+        / v:ident() { ExprKind::Variable(v) }
+
+    rule field() -> (String, Expr)
+        = n:ident() _ ":" ws() e:expr(true) { (n, e) }
+
+    rule funccall() -> ExprKind
+        = n:ident() _ e:args() { ExprKind::FuncCall(n, e) }
+
+    rule args() -> Vec<Expr>
+        = "(" e:((ws() e:expr(true) ws() { e }) ** (",")) ","? ws() ")" { e }
+
+    // The body extends as far as possible, like `if` and `let`.
+    rule lambda(nl: bool) -> ExprKind
+        = "\\" _ a:params() _ "->" ws() b:expr(nl) { ExprKind::Lambda(a, Box::new(b)) }
+
+    rule params() -> Vec<String>
+        = params_list()
+        / a:ident() { vec![a] }
+
+    // Booleans are patterns, rather than names.
+    rule params_list() -> Vec<String>
+        = "(" a:((ws() !boolean() a:ident() ws() { a }) ** (",")) ","? ws() ")" { a }
+
+    // Arms can't continue over lines, so that the next arm is not taken as an operand.
+    rule match_expr() -> ExprKind
+        = "match" wspace() e:expr(true) ws() "{" ws()
+          arms:(arm() ** arm_sep()) arm_sep()? ws() "}" {
+            ExprKind::Match(Box::new(e), arms)
+        }
+
+    rule arm() -> (Pattern, Expr)
+        = p:pattern() ws() "=>" ws() e:expr(false) { (p, e) }
+
+    rule arm_sep() = _ ("," / newline()) ws()
+
+    rule pattern() -> Pattern
+        = s:position!() k:pattern_kind() t:position!() {
+            Pattern { kind: k, span: lines.span(s, t) }
+        }
 
-    rule funccall() -> Expr
-        = n:ident() _ "(" e:((_ e:expr() _ { e }) ** (",")) ","? _ ")" {
-            Expr::FuncCall(n, e)
+    rule pattern_kind() -> PatternKind
+        = "_" !['a'..='z' | '_'] { PatternKind::Wildcard }
+        / n:cname() _ "(" ps:((ws() p:pattern() ws() { p }) ** ",") ","? ws() ")" {
+            PatternKind::Constructor(n, ps)
+        }
+        / n:cname() { PatternKind::Constructor(n, Vec::new()) }
+        / "(" ws() p:pattern() ws() ")" { p.kind }
+        / "(" ps:((ws() p:pattern() ws() { p }) **<2,> ",") ","? ws() ")" {
+            PatternKind::Tuple(ps)
         }
+        / s:string() { PatternKind::Str(s) }
+        / b:boolean() { PatternKind::Bool(b) }
+        / "-" _ n:$(['0'..='9']+) { PatternKind::Value(format!("-{}", n)) }
+        / n:number() { PatternKind::Value(n) }
+        / v:ident() { PatternKind::Variable(v) }
+
+    // `then` and `else` can't start a statement, so newlines are allowed around them.
+    rule if_expr(nl: bool) -> ExprKind
+        = "if" wspace() c:expr(true) wspace()
+          "then" wspace() t:expr(true) wspace()
+          "else" wspace() f:expr(nl) { ExprKind::If(Box::new(c), Box::new(t), Box::new(f)) }
+
+    // `in` can't start a statement, so newlines are allowed around it.
+    rule let_expr(nl: bool) -> ExprKind
+        = "let" wspace() v:ident() ws() "=" ws() e:expr(true) wspace()
+          "in" wspace() b:expr(nl) { ExprKind::Let(v, Box::new(e), Box::new(b)) }
 
-    rule if_expr() -> Expr
-        = "if" space() c:expr() space()
-          "then" space() t:expr() space()
-          "else" space() f:expr() { Expr::If(Box::new(c), Box::new(t), Box::new(f)) }
+    rule boolean() -> bool
+        = "true" !ident() { true }
+        / "false" !ident() { false }
 
     rule ident() -> String
-        = s:$(['a'..='z' | '_']*) { String::from(s) }
+        = s:$(['a'..='z' | '_']+) { String::from(s) }
 
-    rule number() -> i32
-        = "-" _ n:unsigned() { -n }
-        / ("+"/"") _ n:unsigned() { n }
+    // The name of constructors and data types.
+    rule cname() -> String
+        = s:$(['A'..='Z'] ['a'..='z' | 'A'..='Z' | '_']*) { String::from(s) }
+
+    // `-` is parsed as a prefix operator, and the range is checked by the compiler.
+    rule number() -> String
+        = ("+"/"") _ n:$(['0'..='9']+) { String::from(n) }
+
+    rule float() -> String
+        = ("+"/"") _ n:$(['0'..='9']+ ("." ['0'..='9']+ exponent()? / exponent())) {
+            String::from(n)
+        }
+
+    // Newlines must be escaped.
+    rule string() -> String
+        = "\"" cs:string_char()* "\"" { cs.into_iter().collect() }
+
+    rule string_char() -> char
+        = "\\" c:escape() { c }
+        / c:$(!['"' | '\\' | '\n' | '\r'] [_]) { c.chars().next().unwrap() }
+
+    rule escape() -> char
+        = quiet!{
+            "n" { '\n' }
+            / "t" { '\t' }
+            / "r" { '\r' }
+            / "0" { '\0' }
+            / "\\" { '\\' }
+            / "\"" { '"' }
+            / "u{" h:$(['0'..='9' | 'a'..='f' | 'A'..='F']*<1,6>) "}" {?
+                u32::from_str_radix(h, 16).ok().and_then(char::from_u32).ok_or("")
+            }
+        }
+        / expected!("an escape sequence")
 
-    rule unsigned() -> i32
-        = n:$(['0'..='9']+) {? n.parse().or(Err("Integer Parsing Error"))}
+    rule exponent()
+        = ['e' | 'E'] ['+' | '-']? ['0'..='9']+
 
 }}
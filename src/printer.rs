@@ -1,14 +1,16 @@
+use crate::Value;
 use std::fmt;
 
 /// The printer to print evaluated value.
 pub trait Printer {
     /// The error type that printer will provide.
-    type Err: std::error::Error + 'static;
-    /// Print integer value.
-    fn print(&mut self, v: i32) -> Result<(), Self::Err>;
+    type Err: std::error::Error + Send + Sync + 'static;
+    /// Print the evaluated value.
+    fn print(&mut self, v: &Value) -> Result<(), Self::Err>;
 }
 
-/// The default printer implementation which prints to stdout.
+/// The default printer implementation which prints to stdout. (Strings are printed without
+/// quotes.)
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct StdPrinter;
 
@@ -25,7 +27,7 @@ impl std::error::Error for EmptyError {}
 
 impl Printer for StdPrinter {
     type Err = EmptyError;
-    fn print(&mut self, v: i32) -> Result<(), Self::Err> {
+    fn print(&mut self, v: &Value) -> Result<(), Self::Err> {
         println!("{}", v);
         Ok(())
     }
@@ -0,0 +1,79 @@
+use std::fmt;
+
+/// The location of a statement or an expression in the source code.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The byte offset where the node starts.
+    pub start: usize,
+    /// The byte offset where the node ends. (exclusive)
+    pub end: usize,
+    /// The line number where the node starts. (1-origin)
+    pub line: usize,
+    /// The column number where the node starts. (1-origin, counted by characters)
+    pub column: usize,
+}
+
+impl Span {
+    /// Make the span which covers both `self` and `other`.
+    pub fn to(self, other: Span) -> Self {
+        let (first, last) = if self.start <= other.start {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        Self {
+            end: first.end.max(last.end),
+            ..first
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// The table of line offsets, to convert byte offsets into spans.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Lines<'a> {
+    src: &'a str,
+    starts: Vec<usize>,
+    base: usize,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(src: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            src,
+            starts,
+            base: 0,
+        }
+    }
+
+    /// The table for the slice `&src[base..]`, which produces spans in the whole source.
+    pub fn offset(&self, base: usize) -> Self {
+        Self {
+            base,
+            ..self.clone()
+        }
+    }
+
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let (start, end) = (self.base + start, self.base + end);
+        let line = match self.starts.binary_search(&start) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let column = self.src[self.starts[line]..start].chars().count() + 1;
+        Span {
+            start,
+            end,
+            line: line + 1,
+            column,
+        }
+    }
+}
@@ -0,0 +1,1283 @@
+//! The type inference, which runs between `ir::compile` and `Evaluator::evaluate`.
+//!
+//! Types are inferred by the Hindley-Milner algorithm, where the functions defined by `def`
+//! and the variables bound by `let` are polymorphic. Overloaded operators and builtin functions
+//! restrict their type variables to some types (e.g. numbers for `+`), which stay polymorphic
+//! like `half : Num a => a -> a`. Since the evaluator promotes mixed numbers (`Int` to `Ratio`
+//! to `Float`), so do the arithmetic operators, the comparisons, and the function arguments.
+use crate::ir::{components, Builtin, Case, Decision, Expr, ExprKind, Function, Operator, Program};
+use crate::parser::{TypeExpr, TypeExprKind, UnaryOperator};
+use crate::{ErrorKind, MiniError, MiniResult, Span};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// The inferred type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Type {
+    /// The type variable.
+    Var(usize),
+    Int,
+    Float,
+    Ratio,
+    Bool,
+    Str,
+    Unit,
+    List(Box<Type>),
+    /// The function, with the type of the argument and the result. (Functions taking two or
+    /// more arguments return the functions taking the rest.)
+    Func(Box<Type>, Box<Type>),
+    /// The tuple, whose elements are labeled by their indices.
+    Tuple(Row),
+    Record(Row),
+    /// The data type's name, and the types of its parameters.
+    Data(Rc<str>, Vec<Type>),
+}
+
+/// The elements of the tuple, or the fields of the record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    /// The labels, and the types.
+    pub fields: Vec<(Rc<str>, Type)>,
+    /// The type variable standing for the other fields, if the value may have more fields.
+    /// (e.g. the argument of `def getx(p) = p.x`)
+    pub rest: Option<usize>,
+}
+
+/// The inferred type of the function defined by `def`, or the variable bound by `let`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub ty: Type,
+    /// The classes restricting the type variables, like `Num` of `a` in `Num a => a -> a`.
+    pub classes: Vec<(String, usize)>,
+    /// The location of the definition.
+    pub span: Span,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&show(self, &mut Vec::new(), 0))
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names = Vec::new();
+        let ty = show(&self.ty, &mut names, 0);
+        let mut classes = self.classes.iter().collect::<Vec<_>>();
+        classes.sort_by_key(|(_, v)| names.iter().position(|n| n == v));
+        let classes = classes
+            .into_iter()
+            .map(|(c, v)| format!("{} {}", c, show(&Type::Var(*v), &mut names, 0)))
+            .collect::<Vec<_>>();
+        match classes.is_empty() {
+            true => write!(f, "{} : {}", self.name, ty),
+            false => write!(f, "{} : {} => {}", self.name, classes.join(", "), ty),
+        }
+    }
+}
+
+/// Show the type, where type variables are named in the order of `names`, and the operators
+/// weaker than `prec` are parenthesized. (0: functions, 1: data types with arguments)
+fn show(ty: &Type, names: &mut Vec<usize>, prec: u8) -> String {
+    match ty {
+        Type::Var(v) => {
+            let i = names.iter().position(|n| n == v).unwrap_or_else(|| {
+                names.push(*v);
+                names.len() - 1
+            });
+            let letter = char::from(b'a' + (i % 26) as u8);
+            match i / 26 {
+                0 => letter.to_string(),
+                n => format!("{}{}", letter, n),
+            }
+        }
+        Type::Int => String::from("Int"),
+        Type::Float => String::from("Float"),
+        Type::Ratio => String::from("Ratio"),
+        Type::Bool => String::from("Bool"),
+        Type::Str => String::from("String"),
+        Type::Unit => String::from("()"),
+        Type::List(t) => format!("[{}]", show(t, names, 0)),
+        Type::Func(p, r) if prec < 1 => format!("{} -> {}", show(p, names, 1), show(r, names, 0)),
+        Type::Data(n, args) if args.is_empty() || prec < 2 => args
+            .iter()
+            .fold(n.to_string(), |s, a| format!("{} {}", s, show(a, names, 2))),
+        Type::Tuple(row) => {
+            let mut fields = row.fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|(l, _)| l.parse::<usize>().unwrap_or_default());
+            let mut elems = fields
+                .into_iter()
+                .map(|(_, t)| show(t, names, 0))
+                .collect::<Vec<_>>();
+            if row.rest.is_some() {
+                elems.push(String::from(".."));
+            }
+            format!("({})", elems.join(", "))
+        }
+        Type::Record(row) => {
+            let mut fields = row.fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|(l, _)| l.clone());
+            let mut fields = fields
+                .into_iter()
+                .map(|(l, t)| format!("{}: {}", l, show(t, names, 0)))
+                .collect::<Vec<_>>();
+            if row.rest.is_some() {
+                fields.push(String::from(".."));
+            }
+            format!("{{{}}}", fields.join(", "))
+        }
+        ty => format!("({})", show(ty, names, 0)),
+    }
+}
+
+/// The level of type variables quantified by the type scheme.
+const GENERIC: usize = usize::MAX;
+
+/// The state of the type variable.
+enum Slot {
+    Bound(Type),
+    /// The variable not bound yet, with the depth of `let`s where it's made, and the types it
+    /// may be bound to.
+    Free {
+        level: usize,
+        class: Option<Class>,
+    },
+}
+
+/// The set of types which the overloaded operators take, by the bits of `Class::head`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Class(u8);
+
+impl Class {
+    const INT: u8 = 1;
+    const FLOAT: u8 = 2;
+    const RATIO: u8 = 4;
+    const STR: u8 = 8;
+    const BOOL: u8 = 16;
+    const UNIT: u8 = 32;
+    const LIST: u8 = 64;
+
+    const NUM: Self = Self(Self::INT | Self::FLOAT | Self::RATIO);
+    const ORD: Self = Self(Self::NUM.0 | Self::STR);
+    const EQ: Self = Self(Self::ORD.0 | Self::BOOL | Self::UNIT);
+    const BITS: Self = Self(Self::INT | Self::BOOL);
+    const RATIONAL: Self = Self(Self::INT | Self::RATIO);
+    const SIZED: Self = Self(Self::STR | Self::LIST);
+
+    fn head(ty: &Type) -> u8 {
+        match ty {
+            Type::Int => Self::INT,
+            Type::Float => Self::FLOAT,
+            Type::Ratio => Self::RATIO,
+            Type::Str => Self::STR,
+            Type::Bool => Self::BOOL,
+            Type::Unit => Self::UNIT,
+            Type::List(_) => Self::LIST,
+            _ => 0,
+        }
+    }
+
+    fn contains(self, ty: &Type) -> bool {
+        self.0 & Self::head(ty) != 0
+    }
+
+    /// The only type of the class with one type, where `elem` makes the type of list elements.
+    fn only<F: FnOnce() -> Type>(self, elem: F) -> Type {
+        match self.0 {
+            b if b & Self::INT != 0 => Type::Int,
+            b if b & Self::STR != 0 => Type::Str,
+            b if b & Self::BOOL != 0 => Type::Bool,
+            b if b & Self::UNIT != 0 => Type::Unit,
+            b if b & Self::FLOAT != 0 => Type::Float,
+            b if b & Self::RATIO != 0 => Type::Ratio,
+            _ => Type::List(Box::new(elem())),
+        }
+    }
+
+    /// The name in the signatures. (Classes with one type never remain.)
+    fn name(self) -> &'static str {
+        match self {
+            Self::NUM => "Num",
+            Self::ORD => "Ord",
+            Self::EQ => "Eq",
+            Self::BITS => "Bits",
+            Self::RATIONAL => "Rational",
+            Self::SIZED => "Sized",
+            _ => unreachable!("the classes are closed under intersection"),
+        }
+    }
+
+    fn describe(self) -> String {
+        let s = match self {
+            Self::NUM => "a number",
+            Self::ORD => "a number or a string",
+            Self::EQ => "a comparable type",
+            Self::BITS => "an integer or a boolean",
+            Self::RATIONAL => "an integer or a rational",
+            Self::SIZED => "a string or a list",
+            _ => {
+                let heads = [
+                    "`Int`", "`Float`", "`Ratio`", "`String`", "`Bool`", "`()`", "a list",
+                ];
+                let heads = (0..heads.len())
+                    .filter(|i| self.0 & (1 << i) != 0)
+                    .map(|i| heads[i])
+                    .collect::<Vec<_>>();
+                return heads.join(" or ");
+            }
+        };
+        String::from(s)
+    }
+}
+
+/// The reason of the unification failure.
+enum Failure {
+    /// The types have different shapes, which are reported as the whole types.
+    Mismatch,
+    /// The descriptions of the expected type and the found one.
+    Describe(String, String),
+    /// The type variable and the type containing it.
+    Infinite(usize, Type),
+}
+
+impl Failure {
+    /// Swap the expected and the found.
+    fn flip(self) -> Self {
+        match self {
+            Self::Describe(e, f) => Self::Describe(f, e),
+            f => f,
+        }
+    }
+}
+
+/// The state of the inference.
+struct Infer<'a> {
+    program: &'a Program,
+    slots: Vec<Slot>,
+    /// The depth of `let`s (and definitions) being inferred.
+    level: usize,
+    /// The type schemes of the functions defined by `def`.
+    funcs: Vec<Type>,
+    /// The type schemes of the global variables.
+    globals: Vec<Type>,
+    /// The type schemes of the constructors, as functions taking the fields.
+    ctors: Vec<Type>,
+}
+
+/// The types of the local variables in the frame, which may be type schemes.
+type Locals = Vec<Option<Type>>;
+
+impl<'a> Infer<'a> {
+    fn var(&mut self, level: usize, class: Option<Class>) -> usize {
+        self.slots.push(Slot::Free { level, class });
+        self.slots.len() - 1
+    }
+
+    fn fresh(&mut self) -> Type {
+        Type::Var(self.var(self.level, None))
+    }
+
+    fn fresh_in(&mut self, class: Class) -> Type {
+        Type::Var(self.var(self.level, Some(class)))
+    }
+
+    fn generic(&mut self) -> Type {
+        Type::Var(self.var(GENERIC, None))
+    }
+
+    /// Follow the bound type variables.
+    fn find(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        while let Type::Var(v) = ty {
+            match &self.slots[v] {
+                Slot::Bound(t) => ty = t.clone(),
+                Slot::Free { .. } => break,
+            }
+        }
+        ty
+    }
+
+    /// Collect the fields of the row, following the variable of the rest fields.
+    fn row(&self, row: &Row) -> Row {
+        let mut fields = row.fields.clone();
+        let mut rest = row.rest;
+        while let Some(v) = rest {
+            match self.find(&Type::Var(v)) {
+                Type::Tuple(r) | Type::Record(r) => {
+                    fields.extend(r.fields);
+                    rest = r.rest;
+                }
+                Type::Var(w) => {
+                    rest = Some(w);
+                    break;
+                }
+                _ => unreachable!("rows are bound only to rows"),
+            }
+        }
+        Row { fields, rest }
+    }
+
+    /// Resolve all bound type variables in the type.
+    fn zonk(&self, ty: &Type) -> Type {
+        match self.find(ty) {
+            Type::List(t) => Type::List(Box::new(self.zonk(&t))),
+            Type::Func(p, r) => Type::Func(Box::new(self.zonk(&p)), Box::new(self.zonk(&r))),
+            Type::Tuple(row) => Type::Tuple(self.zonk_row(&row)),
+            Type::Record(row) => Type::Record(self.zonk_row(&row)),
+            Type::Data(n, args) => Type::Data(n, args.iter().map(|t| self.zonk(t)).collect()),
+            ty => ty,
+        }
+    }
+
+    fn zonk_row(&self, row: &Row) -> Row {
+        let row = self.row(row);
+        Row {
+            fields: row
+                .fields
+                .iter()
+                .map(|(l, t)| (Rc::clone(l), self.zonk(t)))
+                .collect(),
+            rest: row.rest,
+        }
+    }
+
+    /// Describe the type quoted, for error messages.
+    fn describe(&self, ty: &Type) -> String {
+        format!("`{}`", self.zonk(ty))
+    }
+
+    fn unify(&mut self, expected: &Type, found: &Type) -> Result<(), Failure> {
+        let (expected, found) = (self.find(expected), self.find(found));
+        match (&expected, &found) {
+            (Type::Var(v), _) => self.bind(*v, found),
+            (_, Type::Var(w)) => self.bind(*w, expected).map_err(Failure::flip),
+            (Type::List(a), Type::List(b)) => self.unify(a, b),
+            (Type::Func(p, r), Type::Func(q, s)) => {
+                self.unify(p, q)?;
+                self.unify(r, s)
+            }
+            (Type::Tuple(a), Type::Tuple(b)) => self.unify_rows(a, b, Type::Tuple),
+            (Type::Record(a), Type::Record(b)) => self.unify_rows(a, b, Type::Record),
+            (Type::Data(n, xs), Type::Data(m, ys)) if n == m => {
+                for (x, y) in xs.iter().zip(ys) {
+                    self.unify(x, y)?;
+                }
+                Ok(())
+            }
+            (Type::Int, Type::Int)
+            | (Type::Float, Type::Float)
+            | (Type::Ratio, Type::Ratio)
+            | (Type::Bool, Type::Bool)
+            | (Type::Str, Type::Str)
+            | (Type::Unit, Type::Unit) => Ok(()),
+            _ => Err(Failure::Mismatch),
+        }
+    }
+
+    /// Unify the rows, where the fields only in one side are added to the rest of the other.
+    fn unify_rows(&mut self, a: &Row, b: &Row, make: fn(Row) -> Type) -> Result<(), Failure> {
+        let (a, b) = (self.row(a), self.row(b));
+        let mut only_a = Vec::new();
+        for (label, t) in &a.fields {
+            match b.fields.iter().find(|(l, _)| l == label) {
+                Some((_, u)) => self.unify(t, u)?,
+                None => only_a.push((Rc::clone(label), t.clone())),
+            }
+        }
+        let only_b = b
+            .fields
+            .iter()
+            .filter(|(l, _)| a.fields.iter().all(|(m, _)| l != m))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // The rest fields are `fields` and the variable `rest`.
+        let rest = |fields: Vec<(Rc<str>, Type)>, rest: Option<usize>| match (fields.len(), rest) {
+            (0, Some(r)) => Type::Var(r),
+            _ => make(Row { fields, rest }),
+        };
+        match (a.rest, b.rest) {
+            (None, None) if only_a.is_empty() && only_b.is_empty() => Ok(()),
+            (Some(v), None) if only_a.is_empty() => self.bind(v, rest(only_b, None)),
+            (None, Some(w)) if only_b.is_empty() => self.bind(w, rest(only_a, None)),
+            (Some(v), Some(w)) if v == w && only_a.is_empty() && only_b.is_empty() => Ok(()),
+            (Some(v), Some(w)) if v != w => {
+                let level = self.level_of(v).min(self.level_of(w));
+                let r = self.var(level, None);
+                self.bind(v, rest(only_b, Some(r)))?;
+                self.bind(w, rest(only_a, Some(r)))
+            }
+            _ => Err(Failure::Mismatch),
+        }
+    }
+
+    fn level_of(&self, v: usize) -> usize {
+        match self.slots[v] {
+            Slot::Free { level, .. } => level,
+            Slot::Bound(_) => unreachable!("already followed"),
+        }
+    }
+
+    /// Bind the free type variable `v` to the type, which must be in its class.
+    fn bind(&mut self, v: usize, ty: Type) -> Result<(), Failure> {
+        let (level, class) = match self.slots[v] {
+            Slot::Free { level, class } => (level, class),
+            Slot::Bound(_) => unreachable!("already followed"),
+        };
+        if let Type::Var(w) = ty {
+            if v == w {
+                return Ok(());
+            }
+            let (wlevel, wclass) = match self.slots[w] {
+                Slot::Free { level, class } => (level, class),
+                Slot::Bound(_) => unreachable!("already followed"),
+            };
+            let class = match (class, wclass) {
+                (Some(a), Some(b)) if a.0 & b.0 == 0 => {
+                    return Err(Failure::Describe(a.describe(), b.describe()))
+                }
+                (Some(a), Some(b)) => Some(Class(a.0 & b.0)),
+                (a, b) => a.or(b),
+            };
+            let level = level.min(wlevel);
+            self.slots[w] = Slot::Free { level, class };
+            self.slots[v] = Slot::Bound(Type::Var(w));
+            // The class with only one type decides the variable.
+            if let Some(c) = class.filter(|c| c.0.count_ones() == 1) {
+                let ty = c.only(|| Type::Var(self.var(level, None)));
+                self.slots[w] = Slot::Bound(ty);
+            }
+            return Ok(());
+        }
+        if self.occurs(v, level, &ty) {
+            return Err(Failure::Infinite(v, ty));
+        }
+        if let Some(c) = class.filter(|c| !c.contains(&ty)) {
+            return Err(Failure::Describe(c.describe(), self.describe(&ty)));
+        }
+        self.slots[v] = Slot::Bound(ty);
+        Ok(())
+    }
+
+    /// Whether `v` occurs in the type, lowering the levels of the variables in it to `level`.
+    fn occurs(&mut self, v: usize, level: usize, ty: &Type) -> bool {
+        match self.find(ty) {
+            Type::Var(w) => {
+                match &mut self.slots[w] {
+                    Slot::Free { level: l, .. } if *l != GENERIC => *l = (*l).min(level),
+                    _ => {}
+                }
+                v == w
+            }
+            Type::List(t) => self.occurs(v, level, &t),
+            Type::Func(p, r) => self.occurs(v, level, &p) || self.occurs(v, level, &r),
+            Type::Data(_, args) => args.iter().any(|t| self.occurs(v, level, t)),
+            Type::Tuple(row) | Type::Record(row) => {
+                let row = self.row(&row);
+                row.fields.iter().any(|(_, t)| self.occurs(v, level, t))
+                    || row
+                        .rest
+                        .is_some_and(|r| self.occurs(v, level, &Type::Var(r)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Unify the type of the expression at `span` with the expected one.
+    fn unify_at(&mut self, expected: &Type, found: &Type, span: Span) -> MiniResult<()> {
+        // Described before the unification, which may bind some parts of them.
+        let (e, f) = (self.zonk(expected), self.zonk(found));
+        let kind = match self.unify(expected, found) {
+            Ok(()) => return Ok(()),
+            Err(Failure::Mismatch) => {
+                let mut names = Vec::new();
+                ErrorKind::TypeMismatch {
+                    expected: format!("`{}`", show(&e, &mut names, 0)),
+                    found: format!("`{}`", show(&f, &mut names, 0)),
+                }
+            }
+            Err(Failure::Describe(expected, found)) => ErrorKind::TypeMismatch { expected, found },
+            Err(Failure::Infinite(v, ty)) => {
+                let mut names = Vec::new();
+                ErrorKind::InfiniteType {
+                    var: show(&Type::Var(v), &mut names, 0),
+                    ty: show(&self.zonk(&ty), &mut names, 0),
+                }
+            }
+        };
+        Err(MiniError::type_error(kind, span))
+    }
+
+    /// The wider type of the different numeric types, to which the evaluator promotes both.
+    fn promoted(&self, a: &Type, b: &Type) -> Option<Type> {
+        let rank = |t: &Type| match t {
+            Type::Int => Some(0),
+            Type::Ratio => Some(1),
+            Type::Float => Some(2),
+            _ => None,
+        };
+        let (a, b) = (self.find(a), self.find(b));
+        match (rank(&a), rank(&b)) {
+            (Some(i), Some(j)) if i < j => Some(b),
+            (Some(i), Some(j)) if i > j => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Infer the operands of `class` of the operator promoting numbers, like `1 + 0.5`, and
+    /// return their type.
+    fn promote(
+        &mut self,
+        class: Class,
+        lhs: &Expr,
+        rhs: &Expr,
+        locals: &mut Locals,
+    ) -> MiniResult<Type> {
+        let l = self.expr(lhs, locals)?;
+        let expected = self.fresh_in(class);
+        self.unify_at(&expected, &l, lhs.span)?;
+        let r = self.expr(rhs, locals)?;
+        let expected = self.fresh_in(class);
+        self.unify_at(&expected, &r, rhs.span)?;
+        match self.promoted(&l, &r) {
+            Some(ty) => Ok(ty),
+            None => {
+                self.unify_at(&l, &r, rhs.span)?;
+                Ok(l)
+            }
+        }
+    }
+
+    /// Quantify the type variables made inside the current level, keeping their classes.
+    fn generalize(&mut self, ty: &Type) {
+        match self.find(ty) {
+            Type::Var(v) => {
+                if let Slot::Free { level, class } = self.slots[v] {
+                    if level != GENERIC && level > self.level {
+                        self.slots[v] = Slot::Free {
+                            level: GENERIC,
+                            class,
+                        }
+                    }
+                }
+            }
+            Type::List(t) => self.generalize(&t),
+            Type::Func(p, r) => {
+                self.generalize(&p);
+                self.generalize(&r);
+            }
+            Type::Data(_, args) => args.iter().for_each(|t| self.generalize(t)),
+            Type::Tuple(row) | Type::Record(row) => {
+                let row = self.row(&row);
+                row.fields.iter().for_each(|(_, t)| self.generalize(t));
+                if let Some(r) = row.rest {
+                    self.generalize(&Type::Var(r));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace the quantified type variables by fresh ones.
+    fn instantiate(&mut self, ty: &Type) -> Type {
+        self.copy(ty, &mut HashMap::new())
+    }
+
+    fn copy(&mut self, ty: &Type, map: &mut HashMap<usize, usize>) -> Type {
+        match self.find(ty) {
+            Type::Var(v) => Type::Var(self.copy_var(v, map)),
+            Type::List(t) => Type::List(Box::new(self.copy(&t, map))),
+            Type::Func(p, r) => {
+                Type::Func(Box::new(self.copy(&p, map)), Box::new(self.copy(&r, map)))
+            }
+            Type::Data(n, args) => Type::Data(n, args.iter().map(|t| self.copy(t, map)).collect()),
+            Type::Tuple(row) => Type::Tuple(self.copy_row(&row, map)),
+            Type::Record(row) => Type::Record(self.copy_row(&row, map)),
+            ty => ty,
+        }
+    }
+
+    fn copy_var(&mut self, v: usize, map: &mut HashMap<usize, usize>) -> usize {
+        match self.slots[v] {
+            Slot::Free {
+                level: GENERIC,
+                class,
+            } => match map.get(&v) {
+                Some(&w) => w,
+                None => {
+                    let w = self.var(self.level, class);
+                    map.insert(v, w);
+                    w
+                }
+            },
+            _ => v,
+        }
+    }
+
+    fn copy_row(&mut self, row: &Row, map: &mut HashMap<usize, usize>) -> Row {
+        let row = self.row(row);
+        Row {
+            fields: row
+                .fields
+                .iter()
+                .map(|(l, t)| (Rc::clone(l), self.copy(t, map)))
+                .collect(),
+            rest: row.rest.map(|r| self.copy_var(r, map)),
+        }
+    }
+
+    /// The types of constructors, from the declarations of data types.
+    fn datas(&mut self, errors: &mut Vec<MiniError>) {
+        let program = self.program;
+        // The parameters are the type variables in the fields, in the order of appearance.
+        let params = program
+            .datas
+            .iter()
+            .map(|d| {
+                let mut params = Vec::new();
+                for &c in &d.ctors {
+                    for field in &program.ctors[c].fields {
+                        type_vars(field, &mut params);
+                    }
+                }
+                params
+            })
+            .collect::<Vec<_>>();
+        let arities = program
+            .datas
+            .iter()
+            .zip(&params)
+            .map(|(d, ps)| (d.name.as_str(), ps.len()))
+            .collect::<HashMap<_, _>>();
+
+        self.ctors = vec![Type::Unit; program.ctors.len()];
+        for (d, params) in program.datas.iter().zip(params) {
+            let args = params.iter().map(|_| self.generic()).collect::<Vec<_>>();
+            let vars = params
+                .into_iter()
+                .zip(args.iter().cloned())
+                .collect::<HashMap<_, _>>();
+            let result = Type::Data(Rc::from(d.name.as_str()), args);
+            for &c in &d.ctors {
+                let fields = program.ctors[c]
+                    .fields
+                    .iter()
+                    .map(|f| {
+                        self.type_expr(f, &vars, &arities).unwrap_or_else(|e| {
+                            errors.push(e);
+                            self.generic()
+                        })
+                    })
+                    .collect();
+                self.ctors[c] = curry(fields, result.clone());
+            }
+        }
+    }
+
+    /// Convert the type of the field, where `vars` are the parameters of the data type, and
+    /// `arities` are the numbers of parameters of all data types.
+    fn type_expr(
+        &mut self,
+        t: &TypeExpr,
+        vars: &HashMap<&str, Type>,
+        arities: &HashMap<&str, usize>,
+    ) -> MiniResult<Type> {
+        let span = t.span;
+        Ok(match &t.kind {
+            TypeExprKind::Variable(v) => vars[v.as_str()].clone(),
+            TypeExprKind::Named(n, args) => {
+                let builtin = match n.as_str() {
+                    "Int" => Some(Type::Int),
+                    "Float" => Some(Type::Float),
+                    "Ratio" => Some(Type::Ratio),
+                    "Bool" => Some(Type::Bool),
+                    "String" => Some(Type::Str),
+                    _ => None,
+                };
+                let arity = match (arities.get(n.as_str()), &builtin) {
+                    (Some(&arity), _) => arity,
+                    (None, Some(_)) => 0,
+                    (None, None) => {
+                        let name = n.clone();
+                        return Err(MiniError::type_error(
+                            ErrorKind::UndefinedType { name },
+                            span,
+                        ));
+                    }
+                };
+                if args.len() != arity {
+                    let name = n.clone();
+                    return Err(MiniError::type_error(ErrorKind::TypeArgs { name }, span));
+                }
+                match builtin.filter(|_| !arities.contains_key(n.as_str())) {
+                    Some(ty) => ty,
+                    None => Type::Data(
+                        Rc::from(n.as_str()),
+                        args.iter()
+                            .map(|a| self.type_expr(a, vars, arities))
+                            .collect::<MiniResult<_>>()?,
+                    ),
+                }
+            }
+            TypeExprKind::Unit => Type::Unit,
+            TypeExprKind::List(t) => Type::List(Box::new(self.type_expr(t, vars, arities)?)),
+            TypeExprKind::Tuple(ts) => Type::Tuple(Row {
+                fields: ts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| Ok((label(i), self.type_expr(t, vars, arities)?)))
+                    .collect::<MiniResult<_>>()?,
+                rest: None,
+            }),
+            TypeExprKind::Function(p, r) => Type::Func(
+                Box::new(self.type_expr(p, vars, arities)?),
+                Box::new(self.type_expr(r, vars, arities)?),
+            ),
+        })
+    }
+
+    /// Infer the group of definitions depending on each other, which are monomorphic inside
+    /// the group, and generalized after it. (indices of `funcs`, followed by `vars`)
+    fn group(&mut self, group: &[usize]) -> MiniResult<()> {
+        let program = self.program;
+        let defined = program.defined;
+        self.level += 1;
+        for &node in group {
+            let ty = self.fresh();
+            match node.checked_sub(defined) {
+                None => self.funcs[node] = ty,
+                Some(var) => self.globals[var] = ty,
+            }
+        }
+        for &node in group {
+            let (expected, found, span) = match node.checked_sub(defined) {
+                None => {
+                    let func = &program.funcs[node];
+                    let found = self.function(func, Vec::new())?;
+                    (self.funcs[node].clone(), found, func.span)
+                }
+                Some(var) => {
+                    let e = &program.vars[var];
+                    let found = self.expr(e, &mut vec![None; e.frame_size()])?;
+                    (self.globals[var].clone(), found, e.span)
+                }
+            };
+            self.unify_at(&expected, &found, span)?;
+        }
+        self.level -= 1;
+        for &node in group {
+            let ty = match node.checked_sub(defined) {
+                None => self.funcs[node].clone(),
+                Some(var) => self.globals[var].clone(),
+            };
+            self.generalize(&ty);
+        }
+        Ok(())
+    }
+
+    /// Infer the function, with the types of the captured variables.
+    fn function(&mut self, func: &Function, captured: Vec<Type>) -> MiniResult<Type> {
+        let mut locals = vec![None; func.frame];
+        let args = (0..func.arity).map(|_| self.fresh()).collect::<Vec<_>>();
+        for (i, arg) in args.iter().enumerate() {
+            locals[i] = Some(arg.clone());
+        }
+        for (&id, ty) in func.captures.iter().zip(captured) {
+            locals[id] = Some(ty);
+        }
+        let body = self.expr(&func.body, &mut locals)?;
+        // Functions without arguments are called by `f()`.
+        match args.len() {
+            0 => Ok(Type::Func(Box::new(Type::Unit), Box::new(body))),
+            _ => Ok(curry(args, body)),
+        }
+    }
+
+    /// The type of the local variable, which is a fresh one if not bound yet. (the variables
+    /// of the arms never matching)
+    fn local(&mut self, locals: &mut Locals, id: usize) -> Type {
+        match &locals[id] {
+            Some(ty) => ty.clone(),
+            None => {
+                let ty = self.fresh();
+                locals[id] = Some(ty.clone());
+                ty
+            }
+        }
+    }
+
+    fn expr(&mut self, e: &Expr, locals: &mut Locals) -> MiniResult<Type> {
+        let program = self.program;
+        Ok(match &e.kind {
+            ExprKind::Value(_) => self.fresh_in(Class::NUM),
+            ExprKind::Float(_) => Type::Float,
+            ExprKind::Str(_) => Type::Str,
+            ExprKind::Bool(_) => Type::Bool,
+            ExprKind::Unit => Type::Unit,
+            ExprKind::Variable(id) => {
+                let ty = self.local(locals, *id);
+                self.instantiate(&ty)
+            }
+            ExprKind::Global(id) => self.instantiate(&self.globals[*id].clone()),
+            ExprKind::Operation(op, lhs, rhs) => {
+                let (operand, result) = match op {
+                    Operator::Add
+                    | Operator::Sub
+                    | Operator::Mul
+                    | Operator::Div
+                    | Operator::Rem
+                    | Operator::Pow => return self.promote(Class::NUM, lhs, rhs, locals),
+                    Operator::TruncDiv
+                    | Operator::TruncRem
+                    | Operator::EuclidDiv
+                    | Operator::EuclidRem
+                    | Operator::Shl
+                    | Operator::Shr => (Type::Int, Type::Int),
+                    Operator::BitAnd | Operator::BitOr | Operator::BitXor => {
+                        let t = self.fresh_in(Class::BITS);
+                        (t.clone(), t)
+                    }
+                    Operator::Gt | Operator::Ge | Operator::Lt | Operator::Le => {
+                        return self
+                            .promote(Class::ORD, lhs, rhs, locals)
+                            .map(|_| Type::Bool)
+                    }
+                    Operator::Eq | Operator::Neq => {
+                        return self
+                            .promote(Class::EQ, lhs, rhs, locals)
+                            .map(|_| Type::Bool)
+                    }
+                    Operator::Concat => (Type::Str, Type::Str),
+                    Operator::And | Operator::Or => (Type::Bool, Type::Bool),
+                };
+                let found = self.expr(lhs, locals)?;
+                self.unify_at(&operand, &found, lhs.span)?;
+                let found = self.expr(rhs, locals)?;
+                self.unify_at(&operand, &found, rhs.span)?;
+                result
+            }
+            ExprKind::Unary(op, operand) => {
+                let ty = match op {
+                    UnaryOperator::Neg => self.fresh_in(Class::NUM),
+                    UnaryOperator::Not => Type::Bool,
+                    UnaryOperator::BitNot => Type::Int,
+                };
+                let found = self.expr(operand, locals)?;
+                self.unify_at(&ty, &found, operand.span)?;
+                ty
+            }
+            ExprKind::FuncCall(f, args) => {
+                let ty = self.instantiate(&self.funcs[*f].clone());
+                self.call(ty, args, e.span, locals)?
+            }
+            ExprKind::Builtin(b, args) => {
+                let ty = self.builtin(*b);
+                self.apply(ty, args, e.span, locals)?
+            }
+            ExprKind::Call(f, args) => {
+                let ty = self.expr(f, locals)?;
+                self.call(ty, args, e.span, locals)?
+            }
+            ExprKind::Closure(f, _) if *f < program.defined => {
+                self.instantiate(&self.funcs[*f].clone())
+            }
+            // Lifted functions are inferred where they are made, like `let`s.
+            ExprKind::Closure(f, captured) => {
+                let captured = captured.iter().map(|&id| self.local(locals, id)).collect();
+                self.function(&program.funcs[*f], captured)?
+            }
+            ExprKind::If(c, t, f) => {
+                let found = self.expr(c, locals)?;
+                self.unify_at(&Type::Bool, &found, c.span)?;
+                let ty = self.expr(t, locals)?;
+                let found = self.expr(f, locals)?;
+                self.unify_at(&ty, &found, f.span)?;
+                ty
+            }
+            ExprKind::Let(id, bound, body) => {
+                self.level += 1;
+                let ty = self.expr(bound, locals)?;
+                self.level -= 1;
+                self.generalize(&ty);
+                locals[*id] = Some(ty);
+                self.expr(body, locals)?
+            }
+            ExprKind::Tuple(es) => Type::Tuple(Row {
+                fields: es
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| Ok((label(i), self.expr(e, locals)?)))
+                    .collect::<MiniResult<_>>()?,
+                rest: None,
+            }),
+            ExprKind::Record(fs) => Type::Record(Row {
+                fields: fs
+                    .iter()
+                    .map(|(n, e)| Ok((Rc::clone(n), self.expr(e, locals)?)))
+                    .collect::<MiniResult<_>>()?,
+                rest: None,
+            }),
+            // The tuple has at least `i + 1` elements.
+            ExprKind::Project(tuple, i) => {
+                let found = self.expr(tuple, locals)?;
+                let fields = (0..=*i)
+                    .map(|j| (label(j), self.fresh()))
+                    .collect::<Vec<_>>();
+                let ty = fields[*i].1.clone();
+                let rest = Some(self.var(self.level, None));
+                self.unify_at(&Type::Tuple(Row { fields, rest }), &found, tuple.span)?;
+                ty
+            }
+            ExprKind::Field(record, name) => {
+                let found = self.expr(record, locals)?;
+                let ty = self.fresh();
+                let fields = vec![(Rc::clone(name), ty.clone())];
+                let rest = Some(self.var(self.level, None));
+                self.unify_at(&Type::Record(Row { fields, rest }), &found, record.span)?;
+                ty
+            }
+            ExprKind::Construct(c, _, args) => {
+                let ty = self.instantiate(&self.ctors[*c].clone());
+                self.apply(ty, args, e.span, locals)?
+            }
+            ExprKind::Match(decision, arms) => {
+                self.decide(decision, locals, e.span)?;
+                let ty = self.fresh();
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        let found = self.expr(guard, locals)?;
+                        self.unify_at(&Type::Bool, &found, guard.span)?;
+                    }
+                    let found = self.expr(&arm.body, locals)?;
+                    self.unify_at(&ty, &found, arm.body.span)?;
+                }
+                ty
+            }
+        })
+    }
+
+    /// Call the function by `f(args...)`, where `f()` gives the unit value.
+    fn call(
+        &mut self,
+        ty: Type,
+        args: &[Expr],
+        span: Span,
+        locals: &mut Locals,
+    ) -> MiniResult<Type> {
+        match args {
+            [] => self.apply_one(ty, Type::Unit, span, span),
+            args => self.apply(ty, args, span, locals),
+        }
+    }
+
+    /// Apply the arguments one by one.
+    fn apply(
+        &mut self,
+        ty: Type,
+        args: &[Expr],
+        span: Span,
+        locals: &mut Locals,
+    ) -> MiniResult<Type> {
+        args.iter().try_fold(ty, |ty, arg| {
+            let found = self.expr(arg, locals)?;
+            self.apply_one(ty, found, arg.span, span)
+        })
+    }
+
+    fn apply_one(&mut self, ty: Type, arg: Type, arg_span: Span, span: Span) -> MiniResult<Type> {
+        let (param, result) = match self.find(&ty) {
+            Type::Func(p, r) => (*p, *r),
+            Type::Var(_) => {
+                let (p, r) = (self.fresh(), self.fresh());
+                let func = Type::Func(Box::new(p.clone()), Box::new(r.clone()));
+                self.unify_at(&func, &ty, span)?;
+                (p, r)
+            }
+            ty => {
+                let kind = ErrorKind::TypeMismatch {
+                    expected: String::from("a function"),
+                    found: self.describe(&ty),
+                };
+                return Err(MiniError::type_error(kind, span));
+            }
+        };
+        // The numbers are promoted to the wider parameters, like `Int` to `Float`.
+        if self.promoted(&param, &arg) != Some(self.find(&param)) {
+            self.unify_at(&param, &arg, arg_span)?;
+        }
+        Ok(result)
+    }
+
+    fn builtin(&mut self, b: Builtin) -> Type {
+        let func = |p: Type, r: Type| Type::Func(Box::new(p), Box::new(r));
+        let list = |t: Type| Type::List(Box::new(t));
+        match b {
+            Builtin::Floor | Builtin::Round | Builtin::ToInt => {
+                func(self.fresh_in(Class::NUM), Type::Int)
+            }
+            Builtin::ToFloat => func(self.fresh_in(Class::NUM), Type::Float),
+            Builtin::Ratio => {
+                let (n, d) = (
+                    self.fresh_in(Class::RATIONAL),
+                    self.fresh_in(Class::RATIONAL),
+                );
+                func(n, func(d, Type::Ratio))
+            }
+            Builtin::Show => func(self.fresh(), Type::Str),
+            Builtin::Len => func(self.fresh_in(Class::SIZED), Type::Int),
+            Builtin::Nil => list(self.fresh()),
+            Builtin::Cons => {
+                let t = self.fresh();
+                func(t.clone(), func(list(t.clone()), list(t)))
+            }
+            Builtin::Head => {
+                let t = self.fresh();
+                func(list(t.clone()), t)
+            }
+            Builtin::Tail => {
+                let t = list(self.fresh());
+                func(t.clone(), t)
+            }
+            Builtin::IsNil => func(list(self.fresh()), Type::Bool),
+        }
+    }
+
+    /// Infer the types of the values tested by the decision tree, binding the parts into the
+    /// local variables. (The errors are located at `span` of the whole `match`.)
+    fn decide(&mut self, decision: &Decision, locals: &mut Locals, span: Span) -> MiniResult<()> {
+        let (var, cases, default) = match decision {
+            Decision::Arm(_) | Decision::Fail => return Ok(()),
+            Decision::Guard(_, d) => return self.decide(d, locals, span),
+            Decision::Switch(var, cases, default) => (var, cases, default),
+        };
+        let ty = self.local(locals, *var);
+        let found = self.instantiate(&ty);
+        for (case, fields, d) in cases {
+            let (expected, parts) = self.case(case);
+            self.unify_at(&expected, &found, span)?;
+            for (&id, part) in fields.iter().zip(parts) {
+                match locals[id].clone() {
+                    Some(ty) => self.unify_at(&ty, &part, span)?,
+                    None => locals[id] = Some(part),
+                }
+            }
+            self.decide(d, locals, span)?;
+        }
+        match default {
+            Some(d) => self.decide(d, locals, span),
+            None => Ok(()),
+        }
+    }
+
+    /// The type of the values of the case, and the types of the parts.
+    fn case(&mut self, case: &Case) -> (Type, Vec<Type>) {
+        match case {
+            Case::Constructor(c) => {
+                let mut ty = self.instantiate(&self.ctors[*c].clone());
+                let mut parts = Vec::new();
+                for _ in 0..self.program.ctors[*c].arity {
+                    match ty {
+                        Type::Func(p, r) => {
+                            parts.push(*p);
+                            ty = *r;
+                        }
+                        _ => unreachable!("constructors take the fields"),
+                    }
+                }
+                (ty, parts)
+            }
+            Case::Value(_) => (Type::Int, Vec::new()),
+            Case::Str(_) => (Type::Str, Vec::new()),
+            Case::Bool(_) => (Type::Bool, Vec::new()),
+            Case::Tuple(n) => {
+                let parts = (0..*n).map(|_| self.fresh()).collect::<Vec<_>>();
+                let fields = parts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| (label(i), t.clone()))
+                    .collect();
+                (Type::Tuple(Row { fields, rest: None }), parts)
+            }
+        }
+    }
+
+    /// Renumber the type variables from 0, in the order of appearance.
+    fn export(&self, ty: &Type, map: &mut Vec<usize>) -> Type {
+        let var = |v: usize, map: &mut Vec<usize>| {
+            map.iter().position(|&w| w == v).unwrap_or_else(|| {
+                map.push(v);
+                map.len() - 1
+            })
+        };
+        match self.zonk(ty) {
+            Type::Var(v) => Type::Var(var(v, map)),
+            Type::List(t) => Type::List(Box::new(self.export(&t, map))),
+            Type::Func(p, r) => Type::Func(
+                Box::new(self.export(&p, map)),
+                Box::new(self.export(&r, map)),
+            ),
+            Type::Data(n, args) => {
+                Type::Data(n, args.iter().map(|t| self.export(t, map)).collect())
+            }
+            Type::Tuple(row) => Type::Tuple(self.export_row(&row, map, var)),
+            Type::Record(row) => Type::Record(self.export_row(&row, map, var)),
+            ty => ty,
+        }
+    }
+
+    fn export_row<F>(&self, row: &Row, map: &mut Vec<usize>, var: F) -> Row
+    where
+        F: Fn(usize, &mut Vec<usize>) -> usize,
+    {
+        Row {
+            fields: row
+                .fields
+                .iter()
+                .map(|(l, t)| (Rc::clone(l), self.export(t, map)))
+                .collect(),
+            rest: row.rest.map(|r| var(r, map)),
+        }
+    }
+}
+
+/// The label of the tuple's element.
+fn label(i: usize) -> Rc<str> {
+    Rc::from(i.to_string())
+}
+
+/// The function taking `params` one by one.
+fn curry(params: Vec<Type>, result: Type) -> Type {
+    params
+        .into_iter()
+        .rev()
+        .fold(result, |r, p| Type::Func(Box::new(p), Box::new(r)))
+}
+
+/// Collect the type variables in the type, in the order of appearance.
+fn type_vars<'a>(t: &'a TypeExpr, vars: &mut Vec<&'a str>) {
+    match &t.kind {
+        TypeExprKind::Variable(v) if !vars.contains(&v.as_str()) => vars.push(v),
+        TypeExprKind::Named(_, ts) | TypeExprKind::Tuple(ts) => {
+            ts.iter().for_each(|t| type_vars(t, vars))
+        }
+        TypeExprKind::List(t) => type_vars(t, vars),
+        TypeExprKind::Function(p, r) => {
+            type_vars(p, vars);
+            type_vars(r, vars);
+        }
+        _ => {}
+    }
+}
+
+/// The definitions (indices of `funcs` followed by `vars`) referred by the expression, including
+/// ones referred by the lifted functions in it.
+fn references(program: &Program, e: &Expr, refs: &mut Vec<usize>, lifted: &mut Vec<bool>) {
+    let (globals, calls, closures) = e.dependencies();
+    refs.extend(globals.into_iter().map(|g| program.defined + g));
+    for f in calls.into_iter().chain(closures) {
+        match f.checked_sub(program.defined) {
+            None => refs.push(f),
+            Some(i) if !std::mem::replace(&mut lifted[i], true) => {
+                references(program, &program.funcs[f].body, refs, lifted)
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Infer the types of the program, returning the signatures of the functions defined by `def`
+/// and the variables bound by `let`, in the order of the definitions.
+///
+/// ```
+/// use mini_lang::{compile, infer, parse};
+///
+/// let program = compile(parse("def add(x, y) = x + y\n")?)?;
+/// let sigs = infer(&program)?;
+/// assert_eq!(sigs[0].to_string(), "add : Num a => a -> a -> a");
+/// # Ok::<(), mini_lang::MiniError>(())
+/// ```
+pub fn infer(program: &Program) -> MiniResult<Vec<Signature>> {
+    let mut infer = Infer {
+        program,
+        slots: Vec::new(),
+        level: 0,
+        funcs: vec![Type::Unit; program.defined],
+        globals: vec![Type::Unit; program.vars.len()],
+        ctors: Vec::new(),
+    };
+    let mut errors = Vec::new();
+    infer.datas(&mut errors);
+
+    let lifted = program.funcs.len() - program.defined;
+    let bodies = program.funcs[..program.defined]
+        .iter()
+        .map(|f| &f.body)
+        .chain(&program.vars);
+    let edges = bodies
+        .map(|e| {
+            let mut refs = Vec::new();
+            references(program, e, &mut refs, &mut vec![false; lifted]);
+            refs
+        })
+        .collect::<Vec<_>>();
+    for group in components(&edges) {
+        if let Err(e) = infer.group(&group) {
+            errors.push(e);
+            // Not to report errors again where they are used.
+            infer.level = 0;
+            for node in group {
+                let ty = infer.generic();
+                match node.checked_sub(program.defined) {
+                    None => infer.funcs[node] = ty,
+                    Some(var) => infer.globals[var] = ty,
+                }
+            }
+        }
+    }
+    for print in &program.prints {
+        if let Err(e) = infer.expr(print, &mut vec![None; print.frame_size()]) {
+            errors.push(e);
+            infer.level = 0;
+        }
+    }
+    if !errors.is_empty() {
+        return Err(MiniError::from_errors(errors));
+    }
+
+    let funcs = program.funcs[..program.defined]
+        .iter()
+        .zip(&infer.funcs)
+        .map(|(f, ty)| (f.name.clone(), ty, f.span));
+    let vars = program
+        .var_names
+        .iter()
+        .zip(&infer.globals)
+        .zip(&program.vars)
+        .map(|((name, ty), e)| (name.clone(), ty, e.span));
+    let mut sigs = funcs
+        .chain(vars)
+        .map(|(name, ty, span)| {
+            let mut vars = Vec::new();
+            let ty = infer.export(ty, &mut vars);
+            let classes = vars
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &v)| match infer.slots[v] {
+                    Slot::Free { class: Some(c), .. } => Some((String::from(c.name()), i)),
+                    _ => None,
+                })
+                .collect();
+            Signature {
+                name,
+                ty,
+                classes,
+                span,
+            }
+        })
+        .collect::<Vec<_>>();
+    sigs.sort_by_key(|s| s.span.start);
+    Ok(sigs)
+}
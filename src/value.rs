@@ -0,0 +1,315 @@
+use crate::eval::Thunk;
+use crate::{ErrorKind, Int, MiniResult, Ratio};
+use std::fmt;
+use std::rc::Rc;
+
+/// The evaluated value.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Value {
+    Int(Int),
+    /// The exact rational number.
+    Ratio(Ratio),
+    /// The double precision floating point number.
+    Float(f64),
+    Str(Rc<str>),
+    Bool(bool),
+    /// `()`
+    Unit,
+    Func(Rc<Closure>),
+    /// The empty list.
+    Nil,
+    /// The list cell.
+    Cons(Rc<Cons>),
+    /// `(a, b, ...)`
+    Tuple(Rc<Tuple>),
+    /// `{x: a, y: b, ...}`
+    Record(Rc<Record>),
+    /// The value made by the constructor of the data type.
+    Data(Rc<Data>),
+}
+
+/// The list cell, whose head and tail are evaluated on demand by `LazyEval`.
+#[derive(Clone)]
+pub struct Cons {
+    pub(crate) head: Thunk,
+    pub(crate) tail: Thunk,
+}
+
+// The tail may be infinite.
+impl fmt::Debug for Cons {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cons").finish_non_exhaustive()
+    }
+}
+
+/// The tuple, whose elements are evaluated on demand by `LazyEval`.
+#[derive(Clone)]
+pub struct Tuple {
+    pub(crate) elems: Vec<Thunk>,
+}
+
+impl fmt::Debug for Tuple {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tuple")
+            .field("len", &self.elems.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The record, whose fields are evaluated on demand by `LazyEval`.
+#[derive(Clone)]
+pub struct Record {
+    /// The names and values, in the order of the definition.
+    pub(crate) fields: Vec<(Rc<str>, Thunk)>,
+}
+
+impl fmt::Debug for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = self.fields.iter().map(|(n, _)| n).collect::<Vec<_>>();
+        f.debug_struct("Record")
+            .field("fields", &names)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The value made by the constructor, whose fields are evaluated on demand by `LazyEval`.
+#[derive(Clone)]
+pub struct Data {
+    /// The constructor index (in `program.ctors`).
+    pub(crate) ctor: usize,
+    pub(crate) name: Rc<str>,
+    pub(crate) fields: Vec<Thunk>,
+}
+
+impl fmt::Debug for Data {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Data")
+            .field("name", &self.name)
+            .field("len", &self.fields.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The function value, with the variables captured when it was made.
+#[derive(Clone)]
+pub struct Closure {
+    /// The function index (in `program.funcs`).
+    pub(crate) func: usize,
+    /// The captured variables. (in the order of `func.captures`)
+    pub(crate) captured: Vec<Thunk>,
+    /// The arguments applied partially.
+    pub(crate) args: Vec<Thunk>,
+}
+
+// The captured variables may refer to the closure itself.
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Closure")
+            .field("func", &self.func)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Functions are equal only if they are the same value, and `NaN` is not equal to itself.
+/// Lists, tuples, records, and data values are compared by their elements evaluated so far.
+/// (Numbers of different kinds are never equal, unlike `==` in the language.)
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Int(l), Self::Int(r)) => l == r,
+            (Self::Ratio(l), Self::Ratio(r)) => l == r,
+            (Self::Float(l), Self::Float(r)) => l == r,
+            (Self::Str(l), Self::Str(r)) => l == r,
+            (Self::Bool(l), Self::Bool(r)) => l == r,
+            (Self::Unit, Self::Unit) => true,
+            (Self::Func(l), Self::Func(r)) => Rc::ptr_eq(l, r),
+            (Self::Nil, Self::Nil) => true,
+            (Self::Cons(l), Self::Cons(r)) => {
+                let (lh, rh) = (l.head.borrow().peek(), r.head.borrow().peek());
+                let (lt, rt) = (l.tail.borrow().peek(), r.tail.borrow().peek());
+                Rc::ptr_eq(l, r) || (lh.is_some() && lt.is_some() && lh == rh && lt == rt)
+            }
+            (Self::Tuple(l), Self::Tuple(r)) => {
+                l.elems.len() == r.elems.len()
+                    && l.elems
+                        .iter()
+                        .zip(&r.elems)
+                        .all(|(l, r)| evaluated_eq(l, r))
+            }
+            (Self::Record(l), Self::Record(r)) => {
+                l.fields.len() == r.fields.len()
+                    && l.fields
+                        .iter()
+                        .zip(&r.fields)
+                        .all(|((ln, l), (rn, r))| ln == rn && evaluated_eq(l, r))
+            }
+            (Self::Data(l), Self::Data(r)) => {
+                l.ctor == r.ctor
+                    && l.fields
+                        .iter()
+                        .zip(&r.fields)
+                        .all(|(l, r)| evaluated_eq(l, r))
+            }
+            _ => false,
+        }
+    }
+}
+
+fn evaluated_eq(l: &Thunk, r: &Thunk) -> bool {
+    let (l, r) = (l.borrow().peek(), r.borrow().peek());
+    l.is_some() && l == r
+}
+
+/// Format the element of the compound value, where strings are quoted, and the values not
+/// evaluated yet are shown as `_`.
+fn fmt_elem(f: &mut fmt::Formatter, elem: &Thunk) -> fmt::Result {
+    match elem.borrow().peek() {
+        Some(Value::Str(s)) => write!(f, "{:?}", s),
+        Some(v) => write!(f, "{}", v),
+        None => write!(f, "_"),
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int(i) => write!(f, "{}", i),
+            Self::Ratio(r) => write!(f, "{}", r),
+            // Always with the decimal point or the exponent, to be distinguished from integers.
+            Self::Float(x) => write!(f, "{:?}", x),
+            // Without quotes, use `show` to quote.
+            Self::Str(s) => write!(f, "{}", s),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Unit => write!(f, "()"),
+            Self::Func(_) => write!(f, "<function>"),
+            Self::Nil => write!(f, "[]"),
+            // The tail not evaluated yet is shown as `..`.
+            Self::Cons(cell) => {
+                write!(f, "[")?;
+                let mut cell = Rc::clone(cell);
+                loop {
+                    fmt_elem(f, &cell.head)?;
+                    let tail = cell.tail.borrow().peek();
+                    match tail {
+                        Some(Self::Cons(next)) => cell = next,
+                        Some(Self::Nil) => break,
+                        _ => {
+                            write!(f, ", ..")?;
+                            break;
+                        }
+                    }
+                    write!(f, ", ")?;
+                }
+                write!(f, "]")
+            }
+            Self::Tuple(t) => {
+                write!(f, "(")?;
+                for (i, elem) in t.elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    fmt_elem(f, elem)?;
+                }
+                write!(f, ")")
+            }
+            Self::Record(r) => {
+                write!(f, "{{")?;
+                for (i, (name, field)) in r.fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", name)?;
+                    fmt_elem(f, field)?;
+                }
+                write!(f, "}}")
+            }
+            Self::Data(d) if d.fields.is_empty() => write!(f, "{}", d.name),
+            Self::Data(d) => {
+                write!(f, "{}(", d.name)?;
+                for (i, field) in d.fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    fmt_elem(f, field)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl Value {
+    /// The name of the type, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Int(_) => "an integer",
+            Self::Ratio(_) => "a rational",
+            Self::Float(_) => "a float",
+            Self::Str(_) => "a string",
+            Self::Bool(_) => "a boolean",
+            Self::Unit => "a unit",
+            Self::Func(_) => "a function",
+            Self::Nil | Self::Cons(_) => "a list",
+            Self::Tuple(_) => "a tuple",
+            Self::Record(_) => "a record",
+            Self::Data(_) => "a data value",
+        }
+    }
+
+    pub(crate) fn int(self) -> MiniResult<Int> {
+        match self {
+            Self::Int(i) => Ok(i),
+            v => Err(v.mismatch("an integer").into()),
+        }
+    }
+
+    pub(crate) fn bool(self) -> MiniResult<bool> {
+        match self {
+            Self::Bool(b) => Ok(b),
+            v => Err(v.mismatch("a boolean").into()),
+        }
+    }
+
+    pub(crate) fn func(self) -> MiniResult<Rc<Closure>> {
+        match self {
+            Self::Func(f) => Ok(f),
+            v => Err(v.mismatch("a function").into()),
+        }
+    }
+
+    /// The element of the tuple, not evaluated yet.
+    pub(crate) fn element(self, index: usize) -> MiniResult<Thunk> {
+        match self {
+            Self::Tuple(t) => t.elems.get(index).map(Rc::clone).ok_or_else(|| {
+                let name = index.to_string();
+                ErrorKind::NoField { name }.into()
+            }),
+            v => Err(v.mismatch("a tuple").into()),
+        }
+    }
+
+    /// The field of the record, not evaluated yet.
+    pub(crate) fn field(self, name: &str) -> MiniResult<Thunk> {
+        match self {
+            Self::Record(r) => r
+                .fields
+                .iter()
+                .find(|(n, _)| **n == *name)
+                .map(|(_, field)| Rc::clone(field))
+                .ok_or_else(|| {
+                    let name = String::from(name);
+                    ErrorKind::NoField { name }.into()
+                }),
+            v => Err(v.mismatch("a record").into()),
+        }
+    }
+
+    /// The error reporting that `expected` is required instead of the value.
+    pub(crate) fn mismatch(&self, expected: &'static str) -> ErrorKind {
+        ErrorKind::TypeMismatch {
+            expected: String::from(expected),
+            found: String::from(self.type_name()),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::{compile, parse, ErrorKind, Span};
+
+fn show(code: &str) -> Vec<String> {
+    run_values(code)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// The lines of the spans reported as warnings.
+fn warned_lines(code: &str) -> Vec<usize> {
+    let program = compile(parse(code).unwrap()).unwrap();
+    program
+        .warnings
+        .iter()
+        .map(|w| {
+            assert_eq!(w.kind, ErrorKind::Unreachable);
+            let Span { line, .. } = w.span;
+            line
+        })
+        .collect()
+}
+
+#[test]
+fn clauses() {
+    let code = indoc::indoc! {"
+        def fact(0) = 1
+        def fact(n) = n * fact(n - 1)
+        def fibo(0) = 0
+        def fibo(1) = 1
+        def fibo(n) = fibo(n - 1) + fibo(n - 2)
+        print fact(5)
+        print fibo(10)
+    "};
+    assert_eq!(run(code).unwrap(), vec![120, 55]);
+}
+
+#[test]
+fn guards() {
+    let code = indoc::indoc! {"
+        def sign(n) | n > 0 = 1 | n < 0 = -1 | else = 0
+        def collatz(n)
+          | n == 1 = 0
+          | n % 2 == 0 = 1 + collatz(n / 2)
+          | else = 1 + collatz(3 * n + 1)
+        def bits(x) = x | 1
+        print sign(-5)
+        print sign(0)
+        print sign(3)
+        print collatz(6)
+        print bits(4)
+    "};
+    assert_eq!(run(code).unwrap(), vec![-1, 0, 1, 8, 5]);
+}
+
+#[test]
+fn fallthrough() {
+    let code = indoc::indoc! {r#"
+        data Maybe = Nothing | Just(x)
+        def size(n) | n < 10 = "small" | n < 100 = "medium"
+        def size(_) = "large"
+        def or_else(Just(x), _) = x
+        def or_else(Nothing, d) = d
+        def scaled(n) | n > limit = limit | else = n * 2 where limit = 10
+        print size(5) ++ " " ++ size(50) ++ " " ++ size(500)
+        print or_else(Just(1), 2)
+        print or_else(Nothing, 2)
+        print scaled(3)
+        print scaled(30)
+    "#};
+    assert_eq!(show(code), vec!["small medium large", "1", "2", "6", "10"]);
+}
+
+#[test]
+fn warnings() {
+    let code = indoc::indoc! {"
+        def f(0) = 0
+        def f(n) = n
+        def f(1) = 1
+        def g(x) = 1
+        def g(y) = 2
+        print match 1 { _ => 0, 1 => 1 }
+        def h(n) | n > 0 = 1 | else = 0
+        def h(0) = 2
+    "};
+    assert_eq!(warned_lines(code), vec![3, 5, 6, 8]);
+    assert_eq!(warned_lines("def fact(0) = 1\ndef fact(n) = n\n"), vec![]);
+}
+
+#[test]
+fn errors() {
+    let kind_of = |code: &str| run_values(code).unwrap_err().kind().cloned().unwrap();
+    assert_eq!(
+        kind_of("def fact(0) = 1\nprint fact(0)\n"),
+        ErrorKind::NonExhaustive {
+            missing: String::from("fact(_)")
+        }
+    );
+    assert_eq!(
+        kind_of("def not(true, x) = false\ndef not(false, 0) = true\nprint not(true)\n"),
+        ErrorKind::NonExhaustive {
+            missing: String::from("not(false, _)")
+        }
+    );
+    assert_eq!(
+        kind_of("def sign(n) | n > 0 = 1 | n < 0 = -1\nprint sign(1)\n"),
+        ErrorKind::NonExhaustive {
+            missing: String::from("sign(_)")
+        }
+    );
+    assert_eq!(
+        kind_of("def f(0) = 0\ndef f(a, b) = a\nprint f(0)\n"),
+        ErrorKind::ClauseArity {
+            name: String::from("f")
+        }
+    );
+    assert_eq!(
+        kind_of("def f(0) = 0\nprint 1\ndef f(n) = n\n"),
+        ErrorKind::Redefinition {
+            name: String::from("f")
+        }
+    );
+}
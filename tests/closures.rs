@@ -0,0 +1,109 @@
+mod common;
+
+use common::{run, VecPrinter};
+use mini_lang::{execute, execute_unchecked, EagerEval, ErrorKind, LazyEval, Value};
+
+#[test]
+fn lambda() {
+    let code = indoc::indoc! {"
+        let inc = \\x -> x + 1
+        let sub = \\(x, y) -> x - y
+        print inc(2)
+        print sub(7, 2)
+        print (\\x -> x * x)(5)
+        print (\\() -> 42)()
+    "};
+    assert_eq!(run(code).unwrap(), vec![3, 5, 25, 42]);
+}
+
+#[test]
+fn capture() {
+    let code = indoc::indoc! {"
+        def adder(n) = \\x -> x + n
+        def curry(f) = \\x -> \\y -> f(x, y)
+        def mul(x, y) = x * y
+        let add_five = adder(5)
+        print add_five(1)
+        print adder(2)(3)
+        print curry(mul)(6)(7)
+        print let k = 10 in (\\x -> x + k)(1)
+    "};
+    assert_eq!(run(code).unwrap(), vec![6, 5, 42, 11]);
+}
+
+#[test]
+fn higher_order() {
+    let code = indoc::indoc! {"
+        def twice(f, x) = f(f(x))
+        def compose(f, g) = \\x -> f(g(x))
+        def fold(f, acc, n) = if n == 0 then acc else fold(f, f(acc, n), n - 1)
+        def add(x, y) = x + y
+        def double(x) = x * 2
+        print twice(double, 3)
+        print compose(double, \\x -> x + 1)(4)
+        print fold(add, 0, 10)
+        print fold(\\(acc, n) -> acc * n, 1, 5)
+    "};
+    assert_eq!(run(code).unwrap(), vec![12, 10, 55, 120]);
+}
+
+#[test]
+fn shadowing() {
+    let code = indoc::indoc! {"
+        def f(x) = x + 1
+        def apply(f) = f(10)
+        print apply(\\x -> x * 3)
+        print (\\f -> f)(7)
+    "};
+    assert_eq!(run(code).unwrap(), vec![30, 7]);
+}
+
+#[test]
+fn recursive_lambda() {
+    // Making a closure doesn't evaluate its body, so these are not cyclic for the eager evaluator.
+    let code = indoc::indoc! {"
+        let fact = \\n -> if n == 0 then 1 else n * fact(n - 1)
+        let g = f
+        let f = \\n -> if n == 0 then 0 else g(n - 1) + k
+        let y = f(3)
+        let k = 2
+        print fact(5)
+        print y
+    "};
+    assert_eq!(run(code).unwrap(), vec![120, 6]);
+}
+
+#[test]
+fn lazy_capture() {
+    // The captured argument is never forced.
+    let code = indoc::indoc! {"
+        def konst(x) = \\y -> y
+        print konst(1 / 0)(5)
+    "};
+    let mut printer = VecPrinter(Vec::new());
+    execute(code, &LazyEval, &mut printer).unwrap();
+    assert_eq!(printer.0, vec![Value::Int(5.into())]);
+}
+
+#[test]
+fn type_mismatch() {
+    let mut printer = VecPrinter(Vec::new());
+    let err = execute_unchecked("print 1(2)\n", &EagerEval, &mut printer).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::TypeMismatch {
+            expected: String::from("a function"),
+            found: String::from("an integer")
+        })
+    );
+    assert_eq!(err.span().map(|s| s.column), Some(7));
+
+    let err = execute_unchecked("print (\\x -> x) + 1\n", &LazyEval, &mut printer).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a function")
+        })
+    );
+}
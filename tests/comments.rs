@@ -0,0 +1,60 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn statement_end() {
+    let code = indoc::indoc! {"
+        // A leading comment.
+        let x = 5 // five
+        let y = 3 /* three */
+
+        /* A block comment
+           spanning lines. */
+        print x + y // 8
+    "};
+    assert_eq!(run(code).unwrap(), vec![8]);
+}
+
+#[test]
+fn nested_block() {
+    let code = "print 1 /* outer /* inner */ still outer */ + 2\n";
+    assert_eq!(run(code).unwrap(), vec![3]);
+    assert!(run("print 1 /* unterminated /* */\n").is_err());
+}
+
+#[test]
+fn define_arguments() {
+    let code = indoc::indoc! {"
+        def add(/* lhs */ x, y /* rhs */) = x /* plus */ + y
+        print add(3 /* three */, 2)
+    "};
+    assert_eq!(run(code).unwrap(), vec![5]);
+}
+
+#[test]
+fn if_expression() {
+    let code = indoc::indoc! {"
+        print if /* cond */ 3 > 2 \\   // checked first
+          then 5 \\ // the true branch
+          else /* unreachable */ 0
+    "};
+    assert_eq!(run(code).unwrap(), vec![5]);
+}
+
+#[test]
+fn continuation() {
+    let code = indoc::indoc! {"
+        def fibo(n) = \\
+          if n <= 2 \\          // base case
+            then n \\
+            else fibo(n-2) /* left */ + fibo(n-1)
+        print fibo(10)
+    "};
+    assert_eq!(run(code).unwrap(), vec![89]);
+}
+
+#[test]
+fn division_is_not_comment() {
+    assert_eq!(run("print 28 / 5 // 5\n").unwrap(), vec![5]);
+}
@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use mini_lang::{
+    execute_unchecked, execute_with_unchecked, ArithMode, EagerEval, Int, IntType, LazyEval,
+    MiniResult, Numeric, Printer, Value,
+};
+use std::convert::Infallible;
+
+/// The printer which collects printed values.
+pub struct VecPrinter(pub Vec<Value>);
+
+impl Printer for VecPrinter {
+    type Err = Infallible;
+    fn print(&mut self, v: &Value) -> Result<(), Infallible> {
+        self.0.push(v.clone());
+        Ok(())
+    }
+}
+
+/// Run the code by both evaluators, and check they print the same values.
+///
+/// The types are not checked, to test the evaluators on their own. (See `tests/types.rs` for
+/// the type check.)
+pub fn run_values(code: &str) -> MiniResult<Vec<Value>> {
+    let mut eager = VecPrinter(Vec::new());
+    execute_unchecked(code, &EagerEval, &mut eager)?;
+    let mut lazy = VecPrinter(Vec::new());
+    execute_unchecked(code, &LazyEval, &mut lazy)?;
+    assert_eq!(eager.0, lazy.0);
+    Ok(eager.0)
+}
+
+/// Run the code by both evaluators, where all printed values are integers.
+pub fn run(code: &str) -> MiniResult<Vec<i32>> {
+    Ok(run_values(code)?.iter().map(int).collect())
+}
+
+pub fn int(v: &Value) -> i32 {
+    match v {
+        Value::Int(Int::I32(i)) => *i,
+        v => panic!("expected an integer, found {}", v),
+    }
+}
+
+/// Run the code by both evaluators with the integer semantics, and return the printed values
+/// as strings.
+pub fn run_with(code: &str, int: IntType, mode: ArithMode) -> MiniResult<Vec<String>> {
+    let numeric = Numeric { int, mode };
+    let mut eager = VecPrinter(Vec::new());
+    execute_with_unchecked(code, numeric, &EagerEval, &mut eager)?;
+    let mut lazy = VecPrinter(Vec::new());
+    execute_with_unchecked(code, numeric, &LazyEval, &mut lazy)?;
+    assert_eq!(eager.0, lazy.0);
+    Ok(eager.0.iter().map(ToString::to_string).collect())
+}
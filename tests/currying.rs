@@ -0,0 +1,104 @@
+mod common;
+
+use common::{run, VecPrinter};
+use mini_lang::{execute, execute_unchecked, EagerEval, ErrorKind, LazyEval, Value};
+
+#[test]
+fn partial_application() {
+    let code = indoc::indoc! {"
+        def add(x, y) = x + y
+        def sum(x, y, z) = x + y + z
+        let inc = add(1)
+        print inc(2)
+        print add(3)(4)
+        print sum(1)(2)(3)
+        print sum(1, 2)(3)
+        print sum(1)(2, 3)
+        print (\\(x, y) -> x * y)(6)(7)
+    "};
+    assert_eq!(run(code).unwrap(), vec![3, 7, 6, 6, 6, 42]);
+}
+
+#[test]
+fn over_application() {
+    let code = indoc::indoc! {"
+        def adder(n) = \\x -> x + n
+        def konst(x) = \\y -> x
+        print adder(1, 2)
+        print konst(5, 6)
+        print (\\x -> \\y -> \\z -> x - y - z)(10, 2, 3)
+    "};
+    assert_eq!(run(code).unwrap(), vec![3, 5, 5]);
+}
+
+#[test]
+fn higher_order() {
+    let code = indoc::indoc! {"
+        def mul(x, y) = x * y
+        def twice(f, x) = f(f(x))
+        def fold(f, acc, n) = if n == 0 then acc else fold(f, f(acc)(n), n - 1)
+        print twice(mul(3), 2)
+        print fold(mul, 1, 5)
+    "};
+    assert_eq!(run(code).unwrap(), vec![18, 120]);
+}
+
+#[test]
+fn partial_is_not_shared() {
+    let code = indoc::indoc! {"
+        def sub(x, y) = x - y
+        let from_ten = sub(10)
+        print from_ten(1)
+        print from_ten(2)
+    "};
+    assert_eq!(run(code).unwrap(), vec![9, 8]);
+}
+
+#[test]
+fn lazy_arguments() {
+    // The supplied argument is kept as a thunk, and never forced.
+    let code = indoc::indoc! {"
+        def second(x, y) = y
+        let f = second(1 / 0)
+        print f(2)
+    "};
+    let mut printer = VecPrinter(Vec::new());
+    execute(code, &LazyEval, &mut printer).unwrap();
+    assert_eq!(printer.0, vec![Value::Int(2.into())]);
+
+    let mut printer = VecPrinter(Vec::new());
+    let err = execute(code, &EagerEval, &mut printer).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::DivisionByZero {
+            op: mini_lang::Operator::Div
+        })
+    );
+}
+
+#[test]
+fn unsaturated() {
+    let mut printer = VecPrinter(Vec::new());
+    execute(
+        "def add(x, y) = x + y\nprint add(1)\n",
+        &LazyEval,
+        &mut printer,
+    )
+    .unwrap();
+    assert!(matches!(printer.0[..], [Value::Func(_)]));
+
+    let err = execute_unchecked(
+        "def add(x, y) = x + y\nprint add(1) * 2\n",
+        &EagerEval,
+        &mut printer,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a function")
+        })
+    );
+    assert_eq!(err.span().map(|s| (s.line, s.column)), Some((2, 7)));
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::{run, run_values, VecPrinter};
+use mini_lang::{execute_unchecked, ErrorKind, LazyEval};
+
+fn show(code: &str) -> Vec<String> {
+    run_values(code)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn trees() {
+    let code = indoc::indoc! {"
+        data Tree = Leaf | Node(l, v, r)
+        def insert(t, x) = match t {
+          Leaf => Node(Leaf, x, Leaf)
+          Node(l, v, r) => if x < v then Node(insert(l, x), v, r) else Node(l, v, insert(r, x))
+        }
+        def sum(t) = match t { Leaf => 0, Node(l, v, r) => sum(l) + v + sum(r) }
+        def depth(t) = match t {
+          Leaf => 0,
+          Node(l, _, r) => 1 + max(depth(l), depth(r)),
+        }
+        def max(a, b) = if a > b then a else b
+        let t = insert(insert(insert(insert(Leaf, 5), 2), 8), 3)
+        print sum(t)
+        print depth(t)
+    "};
+    assert_eq!(run(code).unwrap(), vec![18, 3]);
+}
+
+#[test]
+fn nested_patterns() {
+    let code = indoc::indoc! {r#"
+        data Maybe = Nothing | Just(x)
+        def flatten(m) = match m {
+          Just(Just(x)) => Just(x)
+          _ => Nothing
+        }
+        def describe(p) = match p {
+          (0, _) => "zero"
+          (n, true) => "yes " ++ show(n)
+          (-1, false) => "minus one"
+          (_, false) => "no"
+        }
+        print flatten(Just(Just(1)))
+        print flatten(Just(Nothing))
+        print describe((0, false))
+        print describe((2, true))
+        print describe((-1, false))
+        print describe((3, false))
+        print match "b" { "a" => 1, "b" => 2, _ => 3 }
+    "#};
+    assert_eq!(
+        show(code),
+        vec![
+            "Just(1)",
+            "Nothing",
+            "zero",
+            "yes 2",
+            "minus one",
+            "no",
+            "2"
+        ]
+    );
+}
+
+#[test]
+fn constructors_as_functions() {
+    let code = indoc::indoc! {"
+        data Pair = Pair(a, b)
+        def apply(f, x) = f(x)
+        print apply(Pair(1), 2)
+        print Pair(1)(2)
+        print map(Pair(0), cons(1, cons(2, nil)))
+        def map(f, xs) = if is_nil(xs) then nil else cons(f(head(xs)), map(f, tail(xs)))
+    "};
+    assert_eq!(
+        show(code),
+        vec!["Pair(1, 2)", "Pair(1, 2)", "[Pair(0, 1), Pair(0, 2)]"]
+    );
+}
+
+#[test]
+fn lazy_fields() {
+    let code = indoc::indoc! {"
+        data Stream = Stream(head, tail)
+        def from(n) = Stream(n, from(n + 1))
+        def nth(s, n) = match s {
+          Stream(h, t) => if n == 0 then h else nth(t, n - 1)
+        }
+        print nth(from(1), 10)
+        print match Stream(1 / 0, 2) { Stream(_, t) => t }
+    "};
+    let mut printer = VecPrinter(Vec::new());
+    execute_unchecked(code, &LazyEval, &mut printer).unwrap();
+    assert_eq!(
+        printer.0.iter().map(common::int).collect::<Vec<_>>(),
+        vec![11, 2]
+    );
+}
+
+#[test]
+fn errors() {
+    let kind_of = |code: &str| run_values(code).unwrap_err().kind().cloned().unwrap();
+    let missing = |s: &str| ErrorKind::NonExhaustive {
+        missing: String::from(s),
+    };
+    let tree = "data Tree = Leaf | Node(l, v, r)\n";
+    assert_eq!(
+        kind_of(&format!("{}print match Leaf {{ Leaf => 0 }}\n", tree)),
+        missing("Node(_, _, _)")
+    );
+    assert_eq!(
+        kind_of(&format!(
+            "{}print match Leaf {{ Node(Leaf, _, _) => 0, Leaf => 1 }}\n",
+            tree
+        )),
+        missing("Node(Node(_, _, _), _, _)")
+    );
+    assert_eq!(
+        kind_of("print match (1, true) { (_, true) => 0 }\n"),
+        missing("(_, false)")
+    );
+    assert_eq!(kind_of("print match 1 { 0 => 0 }\n"), missing("_"));
+    assert_eq!(
+        kind_of("print match 1 { Just(x) => x, _ => 0 }\n"),
+        ErrorKind::UndefinedConstructor {
+            name: String::from("Just")
+        }
+    );
+    assert_eq!(
+        kind_of(&format!(
+            "{}print match Leaf {{ Node(l) => 0, _ => 1 }}\n",
+            tree
+        )),
+        ErrorKind::FieldCount {
+            name: String::from("Node")
+        }
+    );
+    assert_eq!(
+        kind_of("print match (1, 2) { (x, x) => x }\n"),
+        ErrorKind::Redefinition {
+            name: String::from("x")
+        }
+    );
+    assert_eq!(
+        kind_of("data A = X\ndata B = X\nprint 0\n"),
+        ErrorKind::Redefinition {
+            name: String::from("X")
+        }
+    );
+    assert_eq!(
+        kind_of(&format!(
+            "{}print match 1 {{ Leaf => 0, Node(_, _, _) => 1 }}\n",
+            tree
+        )),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a data value"),
+            found: String::from("an integer")
+        }
+    );
+    assert_eq!(
+        kind_of(&format!("{}print Leaf == Leaf\n", tree)),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a comparable value"),
+            found: String::from("a data value")
+        }
+    );
+}
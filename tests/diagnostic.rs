@@ -0,0 +1,133 @@
+mod common;
+
+use common::run;
+use mini_lang::{compile, infer, parse, render, Diagnostic};
+
+#[test]
+fn parse_error() {
+    let code = "print (1 +\n";
+    let err = run(code).unwrap_err();
+    assert_eq!(
+        render(&err, code, false),
+        indoc::indoc! {r#"
+            error: Parse Error
+             --> 2:1
+              |
+            2 |
+              | ^ expected `!`, `"`, `(`, `+`, `-`, `false`, `if`, `let`, `match`, `true`, `{`, `~`, a number, or an identifier
+        "#}
+    );
+}
+
+#[test]
+fn parse_error_in_params() {
+    let code = "def f(x = x\n";
+    let err = run(code).unwrap_err();
+    assert_eq!(
+        render(&err, code, false),
+        indoc::indoc! {"
+            error: Parse Error
+             --> 1:9
+              |
+            1 | def f(x = x
+              |         ^ expected `)` or `,`
+        "}
+    );
+}
+
+#[test]
+fn invalid_escape() {
+    let code = "print \"a\\qb\"\n";
+    let err = run(code).unwrap_err();
+    assert_eq!(
+        render(&err, code, false),
+        indoc::indoc! {r#"
+            error: Parse Error
+             --> 1:10
+              |
+            1 | print "a\qb"
+              |          ^ expected an escape sequence
+        "#}
+    );
+}
+
+#[test]
+fn unterminated_string() {
+    let code = "print \"abc\n";
+    let err = run(code).unwrap_err();
+    assert_eq!(
+        render(&err, code, false),
+        indoc::indoc! {r#"
+            error: Parse Error
+             --> 1:11
+              |
+            1 | print "abc
+              |           ^ expected `"`
+        "#}
+    );
+}
+
+#[test]
+fn redefinition() {
+    let code = indoc::indoc! {"
+        def add(x, y) = x + y
+        let add = 1
+    "};
+    let err = run(code).unwrap_err();
+    assert_eq!(
+        render(&err, code, false),
+        indoc::indoc! {"
+            error: Execution Error: Defined the name twice.
+             --> 2:1
+              |
+            1 | def add(x, y) = x + y
+              | --------------------- first defined here
+            2 | let add = 1
+              | ^^^^^^^^^^^
+        "}
+    );
+}
+
+#[test]
+fn type_error() {
+    let code = "print 1 + true\n";
+    let err = infer(&compile(parse(code).unwrap()).unwrap()).unwrap_err();
+    assert_eq!(
+        render(&err, code, false),
+        indoc::indoc! {"
+            error: Type Error: Expected a number, found `Bool`.
+             --> 1:11
+              |
+            1 | print 1 + true
+              |           ^^^^
+        "}
+    );
+}
+
+#[test]
+fn colored() {
+    let code = "print 1 / 0\n";
+    let err = run(code).unwrap_err();
+    let out = render(&err, code, true);
+    assert!(out.starts_with("\x1b[1;31merror\x1b[0m"));
+    assert!(out.contains("\x1b[1;31m^^^^^\x1b[0m"));
+}
+
+#[test]
+fn warning() {
+    let code = indoc::indoc! {"
+        def f(n) = n
+        def f(0) = 0
+    "};
+    let program = compile(parse(code).unwrap()).unwrap();
+    assert_eq!(
+        Diagnostic::from(&program.warnings[0]).render(code, false),
+        indoc::indoc! {"
+            warning: Never matches, since the earlier patterns cover it.
+             --> 2:1
+              |
+            2 | def f(0) = 0
+              | ^^^^^^^^^^^^
+        "}
+    );
+}
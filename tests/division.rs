@@ -0,0 +1,116 @@
+mod common;
+
+use common::{run, run_with};
+use mini_lang::{ArithMode, ErrorKind, IntType, Operator};
+
+const OPERATORS: [&str; 6] = ["/", "%", "quot", "rem", "div", "mod"];
+
+/// The left side, the right side, and the results of `OPERATORS`.
+#[rustfmt::skip]
+const TABLE: [(i32, i32, [i32; 6]); 12] = [
+    //  l   r       /   %  quot rem div mod
+    (   7,  2,   [  3,  1,  3,  1,  3,  1]),
+    (  -7,  2,   [ -4,  1, -3, -1, -4,  1]),
+    (   7, -2,   [ -4, -1, -3,  1, -3,  1]),
+    (  -7, -2,   [  3, -1,  3, -1,  4,  1]),
+    (   6,  3,   [  2,  0,  2,  0,  2,  0]),
+    (  -6,  3,   [ -2,  0, -2,  0, -2,  0]),
+    (   6, -3,   [ -2,  0, -2,  0, -2,  0]),
+    (  -6, -3,   [  2,  0,  2,  0,  2,  0]),
+    (   0,  5,   [  0,  0,  0,  0,  0,  0]),
+    (   0, -5,   [  0,  0,  0,  0,  0,  0]),
+    (   1,  5,   [  0,  1,  0,  1,  0,  1]),
+    (  -1,  5,   [ -1,  4,  0, -1, -1,  4]),
+];
+
+fn table_code() -> (String, Vec<String>) {
+    let mut code = String::new();
+    let mut expected = Vec::new();
+    for (l, r, results) in TABLE.iter() {
+        for (op, res) in OPERATORS.iter().zip(results) {
+            code.push_str(&format!("print ({}) {} ({})\n", l, op, r));
+            expected.push(res.to_string());
+        }
+    }
+    (code, expected)
+}
+
+#[test]
+fn sign_combinations() {
+    let (code, expected) = table_code();
+    let types = [
+        IntType::I32,
+        IntType::I64,
+        IntType::I128,
+        #[cfg(feature = "bigint")]
+        IntType::Big,
+    ];
+    for int in types {
+        for mode in [
+            ArithMode::Checked,
+            ArithMode::Wrapping,
+            ArithMode::Saturating,
+        ] {
+            assert_eq!(
+                run_with(&code, int, mode).unwrap(),
+                expected,
+                "{:?}, {:?}",
+                int,
+                mode
+            );
+        }
+    }
+}
+
+#[test]
+fn minimum() {
+    let code = indoc::indoc! {"
+        let min = -2147483648
+        print min / 2
+        print min % 3
+        print min / -3
+        print min mod 3
+        print min rem -1
+    "};
+    assert_eq!(
+        run_with(code, IntType::I32, ArithMode::Wrapping).unwrap(),
+        vec!["-1073741824", "1", "715827882", "1", "0"]
+    );
+    assert_eq!(
+        run("print -2147483648 / -1\n").unwrap_err().kind(),
+        Some(&ErrorKind::Overflow { op: Operator::Div })
+    );
+    assert_eq!(
+        run_with(
+            "print -2147483648 div -1\n",
+            IntType::I32,
+            ArithMode::Saturating
+        )
+        .unwrap(),
+        vec!["2147483647"]
+    );
+}
+
+#[test]
+fn division_by_zero() {
+    for op in OPERATORS.iter() {
+        let code = format!("print 1 {} 0\n", op);
+        assert!(matches!(
+            run(&code).unwrap_err().kind(),
+            Some(ErrorKind::DivisionByZero { .. })
+        ));
+    }
+}
+
+#[test]
+fn keywords() {
+    let code = indoc::indoc! {"
+        let div = 7
+        def mod(x) = x quot 2
+        print div div 2
+        print mod(div) rem 2
+        print (div
+          mod 4)
+    "};
+    assert_eq!(run(code).unwrap(), vec![3, 1, 3]);
+}
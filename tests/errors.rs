@@ -0,0 +1,121 @@
+mod common;
+
+use common::run;
+use mini_lang::{ErrorKind, MiniError, Operator};
+
+fn kind_of(code: &str) -> ErrorKind {
+    run(code).unwrap_err().kind().cloned().unwrap()
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MiniError>();
+}
+
+#[test]
+fn undefined() {
+    assert_eq!(
+        kind_of("print x\n"),
+        ErrorKind::UndefinedVariable {
+            name: String::from("x")
+        }
+    );
+    assert_eq!(
+        kind_of("print f(1)\n"),
+        ErrorKind::UndefinedFunction {
+            name: String::from("f")
+        }
+    );
+}
+
+#[test]
+fn over_application() {
+    assert_eq!(
+        kind_of("def add(x, y) = x + y\nprint add(1, 2, 3)\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a function"),
+            found: String::from("an integer"),
+        }
+    );
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(
+        kind_of("print 2147483647 + 1\n"),
+        ErrorKind::Overflow { op: Operator::Add }
+    );
+    assert_eq!(
+        kind_of("print 1 % 0\n"),
+        ErrorKind::DivisionByZero { op: Operator::Rem }
+    );
+}
+
+#[test]
+fn display() {
+    assert_eq!(
+        run("print 1 / 0\n").unwrap_err().to_string(),
+        "Execution Error at 1:7: Overflowed division, or division by zero"
+    );
+}
+
+#[test]
+fn multiple() {
+    let code = indoc::indoc! {"
+        def add(x, y) = x + z
+        let a = add(1, 2)
+        print a + b
+        print f(c)
+    "};
+    let errors = match run(code).unwrap_err() {
+        MiniError::Multiple(errors) => errors,
+        e => panic!("unexpected error: {}", e),
+    };
+    let kinds = errors
+        .iter()
+        .map(|e| e.kind().cloned().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ErrorKind::UndefinedVariable {
+                name: String::from("z")
+            },
+            ErrorKind::UndefinedVariable {
+                name: String::from("b")
+            },
+            ErrorKind::UndefinedVariable {
+                name: String::from("c")
+            },
+            ErrorKind::UndefinedFunction {
+                name: String::from("f")
+            },
+        ]
+    );
+}
+
+#[test]
+fn multiple_syntax_errors() {
+    let code = indoc::indoc! {"
+        let x = 1 +
+        print x
+        def f(x = x
+        let y = 2
+        print (y
+          * 3
+        print y +* 2
+    "};
+    let errors = match run(code).unwrap_err() {
+        MiniError::Multiple(errors) => errors,
+        e => panic!("unexpected error: {}", e),
+    };
+    let lines = errors
+        .iter()
+        .map(|e| match e {
+            MiniError::Parse(e) => e.location.line,
+            e => panic!("unexpected error: {}", e),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![2, 3, 7, 7]);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::{ErrorKind, Value};
+
+fn show(code: &str) -> Vec<String> {
+    run_values(code)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn literals() {
+    let code = indoc::indoc! {"
+        print 3.25
+        print -2.5
+        print 1e-3
+        print 2.5E2
+        print 1e20
+        print 4.0
+    "};
+    assert_eq!(
+        run_values(code).unwrap(),
+        vec![
+            Value::Float(3.25),
+            Value::Float(-2.5),
+            Value::Float(0.001),
+            Value::Float(250.0),
+            Value::Float(1e20),
+            Value::Float(4.0),
+        ]
+    );
+    assert_eq!(show("print 4.0\nprint 1e20\n"), vec!["4.0", "1e20"]);
+}
+
+#[test]
+fn promotion() {
+    let code = indoc::indoc! {"
+        def average(a, b) = (a + b) / 2.0
+        print average(3, 4)
+        print 1 + 0.5
+        print 7.0 / 2
+        print -7.5 % 2
+        print 2 ** 0.5 > 1.41
+        print 1 == 1.0
+        print ratio(1, 2) + 0.25
+    "};
+    assert_eq!(
+        show(code),
+        vec!["3.5", "1.5", "3.5", "0.5", "true", "true", "0.75"]
+    );
+}
+
+#[test]
+fn ieee() {
+    let code = indoc::indoc! {"
+        let inf = 1.0 / 0
+        let nan = 0.0 / 0
+        print inf
+        print -inf
+        print nan == nan
+        print nan != nan
+        print nan < 1.0 || nan >= 1.0
+    "};
+    assert_eq!(show(code), vec!["inf", "-inf", "false", "true", "false"]);
+}
+
+#[test]
+fn conversions() {
+    let code = indoc::indoc! {"
+        print floor(2.7)
+        print floor(-2.5)
+        print round(2.5)
+        print round(-2.5)
+        print round(2.4)
+        print to_int(-2.7)
+        print to_int(7)
+        print floor(ratio(-7, 2))
+        print round(ratio(7, 2))
+        print to_int(ratio(-7, 2))
+    "};
+    assert_eq!(run(code).unwrap(), vec![2, -3, 3, -3, 2, -2, 7, -4, 4, -3]);
+    assert_eq!(show("print to_float(3)\n"), vec!["3.0"]);
+}
+
+#[test]
+fn errors() {
+    let kind_of = |code: &str| run_values(code).unwrap_err().kind().cloned().unwrap();
+    assert_eq!(kind_of("print to_int(1e10)\n"), ErrorKind::IntConversion);
+    assert_eq!(kind_of("print floor(0.0 / 0)\n"), ErrorKind::IntConversion);
+    assert_eq!(
+        kind_of("print 1.5 & 1\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a float")
+        }
+    );
+    assert_eq!(
+        kind_of("print 1.5 + true\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a float"),
+            found: String::from("a boolean")
+        }
+    );
+    assert_eq!(
+        kind_of("print round(true)\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a number"),
+            found: String::from("a boolean")
+        }
+    );
+}
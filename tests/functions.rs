@@ -0,0 +1,124 @@
+mod common;
+
+use common::{run, VecPrinter};
+use mini_lang::{execute, EagerEval, ErrorKind, LazyEval, Value};
+
+#[test]
+fn global_in_body() {
+    let code = indoc::indoc! {"
+        let rate = 3
+        def scale(x) = x * rate
+        let offset = scale(2)
+        def shift(x) = scale(x) + offset
+        print scale(5)
+        print shift(1)
+    "};
+    assert_eq!(run(code).unwrap(), vec![15, 9]);
+}
+
+#[test]
+fn argument_shadows_global() {
+    let code = indoc::indoc! {"
+        let x = 100
+        def f(x) = x + 1
+        print f(1)
+        print x
+    "};
+    assert_eq!(run(code).unwrap(), vec![2, 100]);
+}
+
+#[test]
+fn forward_reference() {
+    let code = indoc::indoc! {"
+        def f(x) = x * y
+        print f(3)
+        print z
+        let y = 2
+        let z = f(y)
+    "};
+    assert_eq!(run(code).unwrap(), vec![6, 4]);
+}
+
+#[test]
+fn mutual_recursion() {
+    let code = indoc::indoc! {"
+        def is_even(n) = if n == 0 then 1 else is_odd(n - 1)
+        def is_odd(n) = if n == 0 then 0 else is_even(n - 1)
+        print is_even(10)
+        print is_odd(7)
+    "};
+    assert_eq!(run(code).unwrap(), vec![1, 1]);
+}
+
+#[test]
+fn cyclic_binding() {
+    let code = indoc::indoc! {"
+        let a = f(1)
+        def f(x) = x + b
+        let b = a * 2
+    "};
+    let mut printer = VecPrinter(Vec::new());
+    let err = execute(code, &EagerEval, &mut printer).unwrap_err();
+    assert_eq!(err.kind(), Some(&ErrorKind::CyclicBinding));
+    assert_eq!(err.span().map(|s| s.line), Some(1));
+
+    // Lazy evaluation fails only if the cycle is forced.
+    let code = indoc::indoc! {"
+        let a = if b > 0 then 1 else b
+        let b = if true then 2 else a
+        print a
+    "};
+    let mut printer = VecPrinter(Vec::new());
+    execute(code, &LazyEval, &mut printer).unwrap();
+    assert_eq!(printer.0, vec![Value::Int(1.into())]);
+}
+
+#[test]
+fn shadowing() {
+    let code = indoc::indoc! {"
+        let x = 1
+        let x = 2
+        print x
+    "};
+    assert_eq!(run(code).unwrap(), vec![2]);
+
+    // Uses refer to the last binding before them, and functions to the one before the
+    // definitions.
+    let code = indoc::indoc! {"
+        let x = 1
+        def f(y) = x * y
+        print x
+        let x = x + 1
+        def g(y) = x * y
+        print x + f(10) + g(100)
+    "};
+    assert_eq!(run(code).unwrap(), vec![1, 212]);
+
+    let code = indoc::indoc! {"
+        print x
+        let x = 1
+        let x = 2
+    "};
+    let err = run(code).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::AmbiguousVariable {
+            name: String::from("x")
+        })
+    );
+    assert_eq!(err.span().map(|s| s.line), Some(1));
+}
+
+#[test]
+fn redefinition() {
+    let code = indoc::indoc! {"
+        let x = 1
+        def x(y) = y
+    "};
+    assert_eq!(
+        run(code).unwrap_err().kind(),
+        Some(&ErrorKind::Redefinition {
+            name: String::from("x")
+        })
+    );
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::{run, run_values, VecPrinter};
+use mini_lang::{execute, ErrorKind, LazyEval, MiniResult, Value};
+
+/// Run the code by the lazy evaluator, and return the printed values as strings.
+fn run_lazy(code: &str) -> MiniResult<Vec<String>> {
+    let mut printer = VecPrinter(Vec::new());
+    execute(code, &LazyEval, &mut printer)?;
+    Ok(printer.0.iter().map(ToString::to_string).collect())
+}
+
+const PRELUDE: &str = indoc::indoc! {"
+    def map(f, xs) = if is_nil(xs) then nil else cons(f(head(xs)), map(f, tail(xs)))
+    def filter(p, xs) =
+      if is_nil(xs) then nil
+      else if p(head(xs)) then cons(head(xs), filter(p, tail(xs)))
+      else filter(p, tail(xs))
+    def take(n, xs) = if n == 0 || is_nil(xs) then nil else cons(head(xs), take(n - 1, tail(xs)))
+    def sum(xs) = if is_nil(xs) then 0 else head(xs) + sum(tail(xs))
+"};
+
+#[test]
+fn finite() {
+    let code = format!(
+        "{}{}",
+        PRELUDE,
+        indoc::indoc! {r#"
+            let xs = cons(1, cons(2, cons(3, nil)))
+            print head(xs)
+            print head(tail(xs))
+            print sum(map(\x -> x * x, xs))
+            print len(xs)
+            print if is_nil(tail(tail(tail(xs)))) then 1 else 0
+        "#}
+    );
+    assert_eq!(run(&code).unwrap(), vec![1, 2, 14, 3, 1]);
+}
+
+#[test]
+fn printing() {
+    let code = indoc::indoc! {r#"
+        print nil
+        print cons(1, cons(2, nil))
+        print cons("a", cons(cons(true, nil), nil))
+        print "xs = " ++ show(cons(1.5, nil))
+    "#};
+    let printed = run_values(code)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        printed,
+        vec!["[]", "[1, 2]", "[\"a\", [true]]", "xs = [1.5]"]
+    );
+    assert_eq!(run_values("print nil\n").unwrap(), vec![Value::Nil]);
+}
+
+#[test]
+fn infinite() {
+    let code = format!(
+        "{}{}",
+        PRELUDE,
+        indoc::indoc! {"
+            def inc(n) = n + 1
+            let nats = cons(0, map(inc, nats))
+            def from(n) = cons(n, from(n + 1))
+            def sieve(xs) =
+              let p = head(xs) in
+              cons(p, sieve(filter(\\n -> n % p != 0, tail(xs))))
+            let primes = sieve(from(2))
+            print take(10, nats)
+            print take(10, primes)
+            print head(tail(tail(nats)))
+        "}
+    );
+    assert_eq!(
+        run_lazy(&code).unwrap(),
+        vec![
+            "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]",
+            "[2, 3, 5, 7, 11, 13, 17, 19, 23, 29]",
+            "2",
+        ]
+    );
+}
+
+#[test]
+fn shared_tails() {
+    // Each tail is evaluated once, so this is linear rather than exponential.
+    let code = format!(
+        "{}{}",
+        PRELUDE,
+        indoc::indoc! {"
+            def zip_with(f, xs, ys) = cons(f(head(xs), head(ys)), zip_with(f, tail(xs), tail(ys)))
+            def add(x, y) = x + y
+            let fibs = cons(0, cons(1, zip_with(add, fibs, tail(fibs))))
+            def nth(n, xs) = if n == 0 then head(xs) else nth(n - 1, tail(xs))
+            print nth(25, fibs)
+        "}
+    );
+    assert_eq!(run_lazy(&code).unwrap(), vec!["75025"]);
+}
+
+#[test]
+fn lazy_elements() {
+    let code = indoc::indoc! {"
+        let xs = cons(1 / 0, cons(2, nil))
+        print head(tail(xs))
+        print len(xs)
+    "};
+    assert_eq!(run_lazy(code).unwrap(), vec!["2", "2"]);
+}
+
+#[test]
+fn errors() {
+    let kind_of = |code: &str| run_values(code).unwrap_err().kind().cloned().unwrap();
+    assert_eq!(kind_of("print head(nil)\n"), ErrorKind::EmptyList);
+    assert_eq!(kind_of("print tail(nil)\n"), ErrorKind::EmptyList);
+    assert_eq!(
+        kind_of("print is_nil(1)\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a list"),
+            found: String::from("an integer")
+        }
+    );
+    assert_eq!(
+        kind_of("print nil + 1\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a list")
+        }
+    );
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::{run, VecPrinter};
+use mini_lang::{execute, EagerEval, ErrorKind, LazyEval, Value};
+
+#[test]
+fn let_in() {
+    let code = indoc::indoc! {"
+        let x = 10
+        print let y = x * 2 in y + 1
+        print let x = 1 in let y = x + 1 in x + y
+        print (let x = 5 in x) + x
+    "};
+    assert_eq!(run(code).unwrap(), vec![21, 3, 15]);
+}
+
+#[test]
+fn where_clause() {
+    let code = indoc::indoc! {"
+        def hyp(a, b) = sa + sb
+          where sa = a * a,
+                sb = b * b
+        def twice(x) = y + y where y = x + 1
+        print hyp(3, 4)
+        print twice(hyp(1, 2))
+    "};
+    assert_eq!(run(code).unwrap(), vec![25, 12]);
+}
+
+#[test]
+fn in_arguments() {
+    let code = indoc::indoc! {"
+        def add(x, y) = x + y
+        def f(n) = add(let m = n * 2 in m, let k = n + 1 in add(k, k))
+        print f(3)
+    "};
+    assert_eq!(run(code).unwrap(), vec![14]);
+}
+
+#[test]
+fn shared() {
+    // Exponential without sharing the bound value.
+    let code = indoc::indoc! {"
+        def f(n) = if n == 0 then 1 else let x = f(n - 1) in x + x
+        print f(30)
+    "};
+    assert_eq!(run(code).unwrap(), vec![1 << 30]);
+}
+
+#[test]
+fn unused() {
+    let code = "print let x = 1 / 0 in 2\n";
+    let mut printer = VecPrinter(Vec::new());
+    execute(code, &LazyEval, &mut printer).unwrap();
+    assert_eq!(printer.0, vec![Value::Int(2.into())]);
+    let err = execute(code, &EagerEval, &mut printer).unwrap_err();
+    assert!(matches!(err.kind(), Some(ErrorKind::DivisionByZero { .. })));
+}
+
+#[test]
+fn scoping() {
+    let code = "print (let x = 1 in x) + x\n";
+    assert_eq!(
+        run(code).unwrap_err().kind(),
+        Some(&ErrorKind::UndefinedVariable {
+            name: String::from("x")
+        })
+    );
+}
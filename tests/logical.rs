@@ -0,0 +1,90 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::{ErrorKind, Value};
+
+fn bools(code: &str) -> Vec<bool> {
+    run_values(code)
+        .unwrap()
+        .into_iter()
+        .map(|v| match v {
+            Value::Bool(b) => b,
+            v => panic!("expected a boolean, found {}", v),
+        })
+        .collect()
+}
+
+#[test]
+fn truth_table() {
+    let code = indoc::indoc! {"
+        print true && true
+        print true && false
+        print false && true
+        print false || false
+        print false || true
+        print true || false
+        print !true
+        print !!true
+    "};
+    assert_eq!(
+        bools(code),
+        vec![true, false, false, false, true, true, false, true]
+    );
+}
+
+#[test]
+fn precedence() {
+    let code = indoc::indoc! {"
+        print true || false && false
+        print (true || false) && false
+        print 1 < 2 && 2 < 3
+        print !false && 1 == 2
+        print !(1 == 2) || false
+    "};
+    assert_eq!(bools(code), vec![true, false, true, false, true]);
+}
+
+#[test]
+fn short_circuit() {
+    let code = indoc::indoc! {"
+        def loop(n) = loop(n + 1)
+        print false && 1 / 0 == 1
+        print true || loop(0)
+        print false && loop(0)
+    "};
+    assert_eq!(bools(code), vec![false, true, false]);
+}
+
+#[test]
+fn recursion() {
+    let code = indoc::indoc! {"
+        def contains(n, d) = n != 0 && (n % 10 == d || contains(n / 10, d))
+        print if contains(12345, 3) then 1 else 0
+        print if contains(12345, 7) then 1 else 0
+    "};
+    assert_eq!(run(code).unwrap(), vec![1, 0]);
+}
+
+#[test]
+fn multiline() {
+    let code = indoc::indoc! {"
+        print (1 < 2 &&
+          2 < 3 ||
+          false)
+    "};
+    assert_eq!(bools(code), vec![true]);
+}
+
+#[test]
+fn type_mismatch() {
+    let err = run_values("print 1 && true\n").unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::TypeMismatch {
+            expected: String::from("a boolean"),
+            found: String::from("an integer")
+        })
+    );
+    let err = run_values("print !0\n").unwrap_err();
+    assert_eq!(err.span().map(|s| (s.start, s.end)), Some((6, 8)));
+}
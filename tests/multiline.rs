@@ -0,0 +1,74 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::Value;
+
+#[test]
+fn parentheses() {
+    let code = indoc::indoc! {"
+        print (1
+          + 2
+          * 3)
+    "};
+    assert_eq!(run(code).unwrap(), vec![7]);
+}
+
+#[test]
+fn arguments() {
+    let code = indoc::indoc! {"
+        def add(
+          x,
+          y,
+        ) = x + y
+        print add(
+          3,
+          2
+        )
+    "};
+    assert_eq!(run(code).unwrap(), vec![5]);
+}
+
+#[test]
+fn operators() {
+    let code = indoc::indoc! {"
+        let x = 1 +
+          2 *
+          3
+        print x ==
+          7
+    "};
+    assert_eq!(run_values(code).unwrap(), vec![Value::Bool(true)]);
+}
+
+#[test]
+fn if_expression() {
+    let code = indoc::indoc! {"
+        def fibo(n) =
+          if n <= 2
+            then n
+            else fibo(n-2) + fibo(n-1)
+        print fibo(10)
+    "};
+    assert_eq!(run(code).unwrap(), vec![89]);
+}
+
+#[test]
+fn statement_boundary() {
+    let code = indoc::indoc! {"
+        let x = 1
+        let y = if x > 0 then 2 else 3
+        print x
+        print y
+    "};
+    assert_eq!(run(code).unwrap(), vec![1, 2]);
+    assert!(run("print 1\n+ 2\n").is_err());
+}
+
+#[test]
+fn continuation() {
+    let code = indoc::indoc! {"
+        print 1 \\
+          + 2
+    "};
+    assert_eq!(run(code).unwrap(), vec![3]);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::run_with;
+use mini_lang::{ArithMode, ErrorKind, IntType, Operator, UnaryOperator};
+
+#[test]
+fn int_types() {
+    let code = "print 2147483647 + 1\nprint -9223372036854775808\n";
+    assert_eq!(
+        run_with(code, IntType::I64, ArithMode::Checked).unwrap(),
+        vec!["2147483648", "-9223372036854775808"]
+    );
+    assert_eq!(
+        run_with("print 2 ** 100\n", IntType::I128, ArithMode::Checked).unwrap(),
+        vec!["1267650600228229401496703205376"]
+    );
+    assert_eq!(
+        run_with("print -2147483648\n", IntType::I32, ArithMode::Checked).unwrap(),
+        vec!["-2147483648"]
+    );
+}
+
+#[test]
+fn literal_out_of_range() {
+    let err = run_with("print 2147483648\n", IntType::I32, ArithMode::Checked).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::LiteralOutOfRange {
+            literal: String::from("2147483648")
+        })
+    );
+    assert_eq!(err.span().map(|s| s.column), Some(7));
+
+    // Only the bare literal takes the sign, so this overflows at runtime instead.
+    let err = run_with("print -(-2147483648)\n", IntType::I32, ArithMode::Checked).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        Some(&ErrorKind::UnaryOverflow {
+            op: UnaryOperator::Neg
+        })
+    );
+}
+
+#[test]
+fn modes() {
+    let code = indoc::indoc! {"
+        print 2147483647 + 1
+        print -2147483647 - 10
+        print 65536 * 65536
+        print 3 ** 40
+        print -(-2147483647 - 1)
+        print -(-2147483648)
+        print 1 << 40
+        print -5 >> 40
+    "};
+    assert_eq!(
+        run_with(code, IntType::I32, ArithMode::Wrapping).unwrap(),
+        vec![
+            "-2147483648",
+            "2147483639",
+            "0",
+            "689956897",
+            "-2147483648",
+            "-2147483648",
+            "0",
+            "-1"
+        ]
+    );
+    assert_eq!(
+        run_with(code, IntType::I32, ArithMode::Saturating).unwrap(),
+        vec![
+            "2147483647",
+            "-2147483648",
+            "2147483647",
+            "2147483647",
+            "2147483647",
+            "2147483647",
+            "2147483647",
+            "-1"
+        ]
+    );
+}
+
+#[test]
+fn mode_errors() {
+    // Division by zero, and negative exponents fail regardless of the mode.
+    for mode in [ArithMode::Wrapping, ArithMode::Saturating] {
+        let err = run_with("print 1 / 0\n", IntType::I64, mode).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            Some(&ErrorKind::DivisionByZero { op: Operator::Div })
+        );
+        let err = run_with("print 2 ** -1\n", IntType::I64, mode).unwrap_err();
+        assert_eq!(err.kind(), Some(&ErrorKind::NegativeExponent));
+    }
+}
+
+#[test]
+fn parse_int_type() {
+    assert_eq!("i128".parse::<IntType>(), Ok(IntType::I128));
+    assert_eq!("wrapping".parse::<ArithMode>(), Ok(ArithMode::Wrapping));
+    assert!("u8".parse::<IntType>().is_err());
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn bigint() {
+    let code = indoc::indoc! {"
+        def fact(n) = if n == 0 then 1 else n * fact(n - 1)
+        print fact(30)
+        print -(2 ** 128) >> 100
+        print 123456789012345678901234567890 % 1000
+        print ~0 & 255
+    "};
+    assert_eq!(
+        run_with(code, IntType::Big, ArithMode::Checked).unwrap(),
+        vec![
+            "265252859812191058636308480000000",
+            "-268435456",
+            "890",
+            "255"
+        ]
+    );
+}
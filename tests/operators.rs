@@ -0,0 +1,123 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::{ErrorKind, Operator, UnaryOperator, Value};
+
+fn kind_of(code: &str) -> ErrorKind {
+    run_values(code).unwrap_err().kind().cloned().unwrap()
+}
+
+#[test]
+fn negation() {
+    let code = indoc::indoc! {"
+        let x = 5
+        print -x
+        print -(x + 2)
+        print - 3
+        print 2 - -x
+        print --x
+        print -2 ** 2
+    "};
+    assert_eq!(run(code).unwrap(), vec![-5, -7, -3, 7, 5, -4]);
+}
+
+#[test]
+fn bitwise() {
+    let code = indoc::indoc! {"
+        print 12 & 10
+        print 12 | 10
+        print 12 ^ 10
+        print ~0
+        print 1 << 4
+        print -16 >> 2
+        print 1 | 2 ^ 3 & 4
+        print 1 + 1 << 2
+    "};
+    assert_eq!(run(code).unwrap(), vec![8, 14, 6, -1, 16, -4, 3, 8]);
+}
+
+#[test]
+fn power() {
+    let code = indoc::indoc! {"
+        print 2 ** 10
+        print 2 ** 3 ** 2
+        print (-3) ** 3
+        print 7 ** 0
+        print 2 * 3 ** 2
+    "};
+    assert_eq!(run(code).unwrap(), vec![1024, 512, -27, 1, 18]);
+}
+
+#[test]
+fn negative_exponent() {
+    assert_eq!(kind_of("print 2 ** -1\n"), ErrorKind::NegativeExponent);
+    assert_eq!(kind_of("print 1 ** -2\n"), ErrorKind::NegativeExponent);
+    let err = run("print 2 ** -1\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution Error at 1:7: Raised an integer to a negative power."
+    );
+}
+
+#[test]
+fn boolean_bitwise() {
+    let code = indoc::indoc! {"
+        print true & false
+        print true | false
+        print true ^ true
+        print (1 < 2) & (2 < 3)
+    "};
+    assert_eq!(
+        run_values(code).unwrap(),
+        vec![
+            Value::Bool(false),
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Bool(true)
+        ]
+    );
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(
+        kind_of("print 2 ** 31\n"),
+        ErrorKind::Overflow { op: Operator::Pow }
+    );
+    assert_eq!(
+        kind_of("print 1 << 32\n"),
+        ErrorKind::Overflow { op: Operator::Shl }
+    );
+    assert_eq!(
+        kind_of("print 3 << 30\n"),
+        ErrorKind::Overflow { op: Operator::Shl }
+    );
+    assert_eq!(
+        kind_of("print 1 >> -1\n"),
+        ErrorKind::Overflow { op: Operator::Shr }
+    );
+    assert_eq!(
+        kind_of("let x = -2147483647 - 1\nprint -x\n"),
+        ErrorKind::UnaryOverflow {
+            op: UnaryOperator::Neg
+        }
+    );
+}
+
+#[test]
+fn type_mismatch() {
+    assert_eq!(
+        kind_of("print ~true\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a boolean")
+        }
+    );
+    assert_eq!(
+        kind_of("print -false\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a boolean")
+        }
+    );
+}
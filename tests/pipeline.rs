@@ -0,0 +1,45 @@
+mod common;
+
+use common::VecPrinter;
+use mini_lang::parser::{self, ExprKind, StmtKind};
+use mini_lang::{compile, parse, EagerEval, Evaluator, Value};
+
+#[test]
+fn stages() {
+    let ast = parse("let x = 2\nprint x * 3\n").unwrap();
+    assert_eq!(ast.len(), 2);
+    match &ast[1].kind {
+        StmtKind::Print(e) => assert!(matches!(e.kind, ExprKind::Operation(..))),
+        _ => panic!("unexpected statement"),
+    }
+
+    let program = compile(ast).unwrap();
+    assert_eq!((program.vars.len(), program.prints.len()), (1, 1));
+
+    let mut printer = VecPrinter(Vec::new());
+    EagerEval.evaluate(program, &mut printer).unwrap();
+    assert_eq!(printer.0, vec![Value::Int(6.into())]);
+}
+
+#[test]
+fn recovering() {
+    let code = indoc::indoc! {"
+        let x = 1
+        print (x
+        def f(x) = x
+        print +
+    "};
+    let (ast, errors) = parser::parse_recovering(code);
+    assert_eq!(errors.len(), 2);
+    let kinds = ast.iter().map(|s| &s.kind).collect::<Vec<_>>();
+    assert!(matches!(
+        kinds[..],
+        [
+            StmtKind::Binding(..),
+            StmtKind::Error,
+            StmtKind::Define(..),
+            StmtKind::Error
+        ]
+    ));
+    assert_eq!(&code[ast[1].span.start..ast[1].span.end], "print (x");
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::{ErrorKind, Operator, Ratio, Value};
+
+fn show(code: &str) -> Vec<String> {
+    run_values(code)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn arithmetic() {
+    let code = indoc::indoc! {"
+        let third = ratio(1, 3)
+        print third
+        print third + ratio(1, 6)
+        print third - 1
+        print third * 3
+        print 2 / third
+        print ratio(4, -6)
+        print ratio(-7, 2) % 2
+        print ratio(2, 3) ** -2
+        print ratio(0, 1) ** 2
+        print ratio(ratio(1, 2), ratio(3, 4))
+    "};
+    assert_eq!(
+        show(code),
+        vec!["1/3", "1/2", "-2/3", "1/1", "6/1", "-2/3", "1/2", "9/4", "0/1", "2/3"]
+    );
+    assert_eq!(
+        run_values("print ratio(6, 4)\n").unwrap(),
+        vec![Value::Ratio(Ratio::new(3, 2).unwrap())]
+    );
+}
+
+#[test]
+fn comparison() {
+    let code = indoc::indoc! {"
+        def bit(b) = if b then 1 else 0
+        print bit(ratio(1, 3) < ratio(1, 2))
+        print bit(ratio(2, 4) == ratio(1, 2))
+        print bit(ratio(4, 2) == 2)
+        print bit(ratio(1, 3) >= 1)
+        print bit(1 != ratio(3, 3))
+    "};
+    assert_eq!(run(code).unwrap(), vec![1, 1, 1, 0, 0]);
+}
+
+#[test]
+fn averages() {
+    let code = indoc::indoc! {"
+        def average(a, b, c) = ratio(a + b + c, 3)
+        print average(1, 2, 4)
+        print round(average(1, 2, 4))
+    "};
+    assert_eq!(show(code), vec!["7/3", "2"]);
+}
+
+#[test]
+fn errors() {
+    let kind_of = |code: &str| run_values(code).unwrap_err().kind().cloned().unwrap();
+    assert_eq!(
+        kind_of("print ratio(1, 0)\n"),
+        ErrorKind::DivisionByZero { op: Operator::Div }
+    );
+    assert_eq!(
+        kind_of("print ratio(1, 2) % 0\n"),
+        ErrorKind::DivisionByZero { op: Operator::Rem }
+    );
+    assert_eq!(
+        kind_of("print ratio(1, 2) ** ratio(1, 2)\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a rational")
+        }
+    );
+    assert_eq!(
+        kind_of("print ratio(1, 2) quot 2\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a rational")
+        }
+    );
+    assert_eq!(
+        kind_of("print ratio(1.0, 2)\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a rational"),
+            found: String::from("a float")
+        }
+    );
+    assert_eq!(
+        kind_of("print ratio(0, 5) ** -2\n"),
+        ErrorKind::DivisionByZero { op: Operator::Pow }
+    );
+    assert_eq!(
+        run_values("print ratio(0, 5) ** -2\n")
+            .unwrap_err()
+            .to_string(),
+        "Execution Error at 1:7: Division by zero in power"
+    );
+    assert_eq!(
+        kind_of("print ratio(2, 3) ** 100\n"),
+        ErrorKind::Overflow { op: Operator::Pow }
+    );
+}
+
+#[test]
+fn builtins_as_values() {
+    let code = indoc::indoc! {"
+        def twice(f, x) = f(f(x))
+        let half = ratio(1)
+        print twice(\\x -> x * half(2), 1)
+        print twice(round, 2.5)
+    "};
+    assert_eq!(show(code), vec!["1/4", "3"]);
+    // Definitions shadow the builtin functions.
+    let code = indoc::indoc! {"
+        def floor(x) = x + 1
+        print floor(1)
+    "};
+    assert_eq!(run(code).unwrap(), vec![2]);
+}
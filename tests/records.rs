@@ -0,0 +1,115 @@
+mod common;
+
+use common::{run, run_values, VecPrinter};
+use mini_lang::{execute, ErrorKind, LazyEval};
+
+fn show(code: &str) -> Vec<String> {
+    run_values(code)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn tuples() {
+    let code = indoc::indoc! {"
+        def divmod(a, b) = (a / b, a % b)
+        def swap(p) = (p.1, p.0)
+        let qr = divmod(17, 5)
+        print qr.0
+        print qr.1
+        print swap(qr).0
+        print divmod(-7, 2).1
+        print ((1, 2), 3).0.1
+    "};
+    assert_eq!(run(code).unwrap(), vec![3, 2, 2, 1, 2]);
+}
+
+#[test]
+fn records() {
+    let code = indoc::indoc! {"
+        def point(x, y) = {x: x, y: y}
+        def norm(p) = p.x * p.x + p.y * p.y
+        let p = point(3, 4)
+        print p.x
+        print norm(p)
+        print {
+          inner: {value: 42},
+        }.inner.value
+    "};
+    assert_eq!(run(code).unwrap(), vec![3, 25, 42]);
+}
+
+#[test]
+fn printing() {
+    let code = indoc::indoc! {r#"
+        print (1, "a", true)
+        print {name: "mini", version: (0, 1), tags: cons(1.5, nil)}
+        print {}
+        print "p = " ++ show({x: 1, y: ratio(1, 2)})
+    "#};
+    assert_eq!(
+        show(code),
+        vec![
+            r#"(1, "a", true)"#,
+            r#"{name: "mini", version: (0, 1), tags: [1.5]}"#,
+            "{}",
+            "p = {x: 1, y: 1/2}",
+        ]
+    );
+}
+
+#[test]
+fn lazy_fields() {
+    let code = indoc::indoc! {"
+        let r = {ok: 1, bad: 1 / 0}
+        let t = (1 / 0, 2)
+        print r.ok
+        print t.1
+    "};
+    let mut printer = VecPrinter(Vec::new());
+    execute(code, &LazyEval, &mut printer).unwrap();
+    assert_eq!(
+        printer.0.iter().map(common::int).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert!(run(code).is_err());
+}
+
+#[test]
+fn errors() {
+    let kind_of = |code: &str| run_values(code).unwrap_err().kind().cloned().unwrap();
+    assert_eq!(
+        kind_of("print (1, 2).2\n"),
+        ErrorKind::NoField {
+            name: String::from("2")
+        }
+    );
+    assert_eq!(
+        kind_of("print {x: 1}.y\n"),
+        ErrorKind::NoField {
+            name: String::from("y")
+        }
+    );
+    assert_eq!(
+        kind_of("print {x: 1, x: 2}\n"),
+        ErrorKind::DuplicateField {
+            name: String::from("x")
+        }
+    );
+    assert_eq!(
+        kind_of("print 1.x\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a record"),
+            found: String::from("an integer")
+        }
+    );
+    assert_eq!(
+        kind_of("print (1, 2) == (1, 2)\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a comparable value"),
+            found: String::from("a tuple")
+        }
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::run;
+use mini_lang::Span;
+
+fn span_of(code: &str) -> Option<Span> {
+    run(code).unwrap_err().span()
+}
+
+#[test]
+fn operation() {
+    let code = indoc::indoc! {"
+        let x = 3
+        print x +
+          1 / (x - 3)
+    "};
+    let span = span_of(code).unwrap();
+    assert_eq!((span.line, span.column), (3, 3));
+    assert_eq!(&code[span.start..span.end], "1 / (x - 3)");
+}
+
+#[test]
+fn function_body() {
+    let code = indoc::indoc! {"
+        def div(x, y) = x / y
+        print div(1, 0)
+    "};
+    let span = span_of(code).unwrap();
+    assert_eq!((span.line, span.column), (1, 17));
+}
+
+#[test]
+fn undefined_variable() {
+    let code = "print 1 + y\n";
+    let err = run(code).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution Error at 1:11: Using undefined variable."
+    );
+}
+
+#[test]
+fn call() {
+    let code = indoc::indoc! {"
+        def add(x, y) = x + y
+        print  add(1, 2)(3)
+    "};
+    let span = span_of(code).unwrap();
+    assert_eq!((span.line, span.column), (2, 8));
+    assert_eq!(&code[span.start..span.end], "add(1, 2)");
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::{parse, ErrorKind, Value};
+
+fn show(code: &str) -> Vec<String> {
+    run_values(code)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn literals() {
+    let code = indoc::indoc! {r#"
+        print "Hello, world!"
+        print ""
+        print "tab\there\nquote\" backslash\\ \u{3bb}"
+        print "// not a comment"
+    "#};
+    assert_eq!(
+        run_values(code).unwrap(),
+        vec![
+            Value::Str("Hello, world!".into()),
+            Value::Str("".into()),
+            Value::Str("tab\there\nquote\" backslash\\ \u{3bb}".into()),
+            Value::Str("// not a comment".into()),
+        ]
+    );
+}
+
+#[test]
+fn concatenation() {
+    let code = indoc::indoc! {r#"
+        def fibo(n) = if n <= 2 then 1 else fibo(n - 2) + fibo(n - 1)
+        def greet(name) = "Hello, " ++ name ++ "!"
+        print "fib(10) = " ++ show(fibo(10))
+        print greet("Mini")
+        print show(1 + 2 == 3) ++
+          show(ratio(1, 2)) ++ show(0.5) ++ show("q")
+    "#};
+    assert_eq!(
+        show(code),
+        vec!["fib(10) = 55", "Hello, Mini!", "true1/20.5\"q\""]
+    );
+}
+
+#[test]
+fn length_and_comparison() {
+    let code = indoc::indoc! {r#"
+        def bit(b) = if b then 1 else 0
+        print len("hello")
+        print len("\u{3bb}x")
+        print len("a" ++ "bc")
+        print bit("abc" == "ab" ++ "c")
+        print bit("abc" != "abd")
+        print bit("abc" < "abd")
+        print bit("b" > "abc")
+        print bit("" <= "a")
+    "#};
+    assert_eq!(run(code).unwrap(), vec![5, 2, 3, 1, 1, 1, 1, 1]);
+}
+
+#[test]
+fn errors() {
+    let kind_of = |code: &str| run_values(code).unwrap_err().kind().cloned().unwrap();
+    assert_eq!(
+        kind_of("print \"a\" ++ 1\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a string"),
+            found: String::from("an integer")
+        }
+    );
+    assert_eq!(
+        kind_of("print \"a\" + 1\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a string")
+        }
+    );
+    assert_eq!(
+        kind_of("print \"a\" == 1\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a string"),
+            found: String::from("an integer")
+        }
+    );
+    assert_eq!(
+        kind_of("print len(42)\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a string"),
+            found: String::from("an integer")
+        }
+    );
+    assert!(parse("print \"bad \\q escape\"\n").is_err());
+    assert!(parse("print \"unterminated\n").is_err());
+}
@@ -0,0 +1,272 @@
+mod common;
+
+use common::{run_values, VecPrinter};
+use mini_lang::{
+    compile, execute, execute_unchecked, infer, parse, EagerEval, ErrorKind, Evaluator, MiniResult,
+    Type, Value,
+};
+
+/// The inferred signatures, shown as `name : type`.
+fn check(code: &str) -> MiniResult<Vec<String>> {
+    let sigs = infer(&compile(parse(code)?)?)?;
+    Ok(sigs.iter().map(ToString::to_string).collect())
+}
+
+fn kind_of(code: &str) -> ErrorKind {
+    check(code).unwrap_err().kind().cloned().unwrap()
+}
+
+fn mismatch(expected: &str, found: &str) -> ErrorKind {
+    ErrorKind::TypeMismatch {
+        expected: String::from(expected),
+        found: String::from(found),
+    }
+}
+
+#[test]
+fn signatures() {
+    let code = indoc::indoc! {r#"
+        def add(x, y) = x + y
+        def fact(0) = 1
+        def fact(n) = n * fact(n - 1)
+        def area(r) = 3.14 * r * r
+        def greet(name) = "Hello, " ++ name
+        def is_even(n) = if n == 0 then true else is_odd(n - 1)
+        def is_odd(n) = if n == 0 then false else is_even(n - 1)
+        def answer() = 42
+        let half = ratio(1, 2)
+    "#};
+    assert_eq!(
+        check(code).unwrap(),
+        vec![
+            "add : Num a => a -> a -> a",
+            "fact : Int -> Int",
+            "area : Float -> Float",
+            "greet : String -> String",
+            "is_even : Num a => a -> Bool",
+            "is_odd : Num a => a -> Bool",
+            "answer : Num a => () -> a",
+            "half : Ratio",
+        ]
+    );
+}
+
+#[test]
+fn polymorphism() {
+    let code = indoc::indoc! {r#"
+        def id(x) = x
+        def compose(f, g) = \x -> f(g(x))
+        def map(f, xs) = if is_nil(xs) then nil else cons(f(head(xs)), map(f, tail(xs)))
+        def lengths(xs) = map(\s -> len(s), xs)
+        let both = (id(1), id("one"))
+        let pairs = let dup = \x -> (x, x) in (dup(true), dup(()))
+        print map(show, cons(1.5, nil))
+    "#};
+    assert_eq!(
+        check(code).unwrap(),
+        vec![
+            "id : a -> a",
+            "compose : (a -> b) -> (c -> a) -> c -> b",
+            "map : (a -> b) -> [a] -> [b]",
+            "lengths : Sized a => [a] -> [Int]",
+            "both : Num a => (a, String)",
+            "pairs : ((Bool, Bool), ((), ()))",
+        ]
+    );
+}
+
+#[test]
+fn records_and_tuples() {
+    let code = indoc::indoc! {"
+        def getx(p) = p.x
+        def norm(p) = p.x * p.x + p.y * p.y
+        def second(t) = t.1
+        def swap(p) = match p { (a, b) => (b, a) }
+        let origin = {x: 0.0, y: 0.0}
+        print getx(origin) + 0.5
+        print norm({x: 1, y: 2, z: ()})
+    "};
+    assert_eq!(
+        check(code).unwrap(),
+        vec![
+            "getx : {x: a, ..} -> a",
+            "norm : Num a => {x: a, y: a, ..} -> a",
+            "second : (a, b, ..) -> b",
+            "swap : (a, b) -> (b, a)",
+            "origin : {x: Float, y: Float}",
+        ]
+    );
+    assert_eq!(
+        kind_of("print {x: 1}.y\n"),
+        mismatch("`{y: a, ..}`", "`{x: b}`")
+    );
+}
+
+#[test]
+fn data_types() {
+    let code = indoc::indoc! {"
+        data Maybe = Nothing | Just(a)
+        data Tree = Leaf | Node(Tree a, a, Tree a)
+        data Shape = Circle(Float) | Rect(Float, Float) | Group([Shape])
+        def insert(t, x) = match t {
+          Leaf => Node(Leaf, x, Leaf)
+          Node(l, v, r) => if x < v then Node(insert(l, x), v, r) else Node(l, v, insert(r, x))
+        }
+        def or_else(Just(x), _) = x
+        def or_else(Nothing, d) = d
+        let found = Just(Just(\"deep\"))
+        let circle = Circle
+    "};
+    assert_eq!(
+        check(code).unwrap(),
+        vec![
+            "insert : Ord a => Tree a -> a -> Tree a",
+            "or_else : Maybe a -> a -> a",
+            "found : Maybe (Maybe String)",
+            "circle : Float -> Shape",
+        ]
+    );
+    assert_eq!(
+        kind_of("data T = A(Foo)\n"),
+        ErrorKind::UndefinedType {
+            name: String::from("Foo")
+        }
+    );
+    assert_eq!(
+        kind_of("data Maybe = Nothing | Just(a)\ndata T = A(Maybe)\n"),
+        ErrorKind::TypeArgs {
+            name: String::from("Maybe")
+        }
+    );
+    assert_eq!(
+        kind_of("data Tree = Leaf | Node(l, v, r)\ndef left(Node(l, _, _)) = left(l)\ndef left(Leaf) = 0\n"),
+        ErrorKind::InfiniteType {
+            var: String::from("a"),
+            ty: String::from("Tree a b c"),
+        }
+    );
+}
+
+#[test]
+fn numeric_literals() {
+    let code = indoc::indoc! {"
+        def half(x) = x / 2
+        def avg(a, b) = (a + b) / 2.0
+        let one = 1
+        print 1 + 0.5
+        print ratio(1, 3) + 1
+        print avg(1, 2)
+    "};
+    assert_eq!(
+        check(code).unwrap(),
+        vec![
+            "half : Num a => a -> a",
+            "avg : Float -> Float -> Float",
+            "one : Num a => a"
+        ]
+    );
+}
+
+#[test]
+fn promotion() {
+    // The checked programs run as the evaluators promote the numbers.
+    let programs = [
+        ("let x = 1\nprint x + 0.5\n", Value::Float(1.5)),
+        ("def half(x) = x / 2\nprint half(3.0)\n", Value::Float(1.5)),
+        ("print ratio(1, 2) == 0.5\n", Value::Bool(true)),
+        (
+            "def area(r) = 3.14 * r * r\nprint area(len(\"a\")) < 4\n",
+            Value::Bool(true),
+        ),
+    ];
+    for (code, val) in programs.iter() {
+        assert!(check(code).is_ok(), "{}", code);
+        assert_eq!(run_values(code).unwrap(), vec![val.clone()]);
+    }
+    assert_eq!(
+        kind_of("def half(x) = x / 2\nprint half(3.0) << 1\n"),
+        mismatch("`Int`", "`Float`")
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        kind_of("print 1 + \"a\"\n"),
+        mismatch("a number", "`String`")
+    );
+    assert_eq!(
+        kind_of("def add(x, y) = x + y\nprint add(1, true)\n"),
+        mismatch("a number", "`Bool`")
+    );
+    assert_eq!(kind_of("print 3(4)\n"), mismatch("`a -> b`", "a number"));
+    assert_eq!(
+        kind_of("print len(())\n"),
+        mismatch("a string or a list", "`()`")
+    );
+    assert_eq!(
+        kind_of("print cons(1, nil) == nil\n"),
+        mismatch("a comparable type", "`[a]`")
+    );
+    assert_eq!(
+        kind_of("def f(x) = x(x)\n"),
+        ErrorKind::InfiniteType {
+            var: String::from("a"),
+            ty: String::from("a -> b"),
+        }
+    );
+
+    let err = check("print 1 + \"a\"\n").unwrap_err();
+    assert!(err.to_string().starts_with("Type Error at "), "{}", err);
+
+    // Errors in different definitions are reported together, and located.
+    let code = "def f(n) = n ++ 1\nprint if true then 1 else \"no\"\n";
+    let err = check(code).unwrap_err();
+    match err {
+        mini_lang::MiniError::Multiple(errors) => {
+            let spans = errors
+                .iter()
+                .map(|e| e.span().map(|s| (s.line, s.column)))
+                .collect::<Vec<_>>();
+            assert_eq!(spans, vec![Some((1, 17)), Some((2, 27))]);
+        }
+        e => panic!("expected multiple errors, found {}", e),
+    }
+}
+
+#[test]
+fn before_evaluation() {
+    // The type error is found without evaluating the infinite loop.
+    let code = "def loop(n) = loop(n + 1)\nprint loop(0)\nprint 1 && true\n";
+    let program = compile(parse(code).unwrap()).unwrap();
+    assert!(infer(&program).is_err());
+
+    let program = compile(parse("print head(cons(1, nil))\n").unwrap()).unwrap();
+    assert_eq!(infer(&program).unwrap(), Vec::new());
+    let mut printer = VecPrinter(Vec::new());
+    EagerEval.evaluate(program, &mut printer).unwrap();
+    assert_eq!(printer.0, vec![Value::Int(1.into())]);
+
+    let program = compile(parse("def id(x) = x\n").unwrap()).unwrap();
+    let ty = Type::Func(Box::new(Type::Var(0)), Box::new(Type::Var(0)));
+    assert_eq!(infer(&program).unwrap()[0].ty, ty);
+
+    // `execute` checks the types first, while `execute_unchecked` fails in the middle.
+    let code = "print 1\nprint 1 + true\n";
+    let mut printer = VecPrinter(Vec::new());
+    let err = execute(code, &EagerEval, &mut printer).unwrap_err();
+    assert_eq!(err.kind(), Some(&mismatch("a number", "`Bool`")));
+    assert!(printer.0.is_empty());
+    let err = execute_unchecked(code, &EagerEval, &mut printer).unwrap_err();
+    assert_eq!(err.kind(), Some(&mismatch("an integer", "a boolean")));
+    assert_eq!(printer.0, vec![Value::Int(1.into())]);
+}
+
+#[test]
+fn shadowing() {
+    // Each binding has its own type.
+    let code = "let x = 1\nlet x = \"one\"\nprint len(x)\n";
+    let mut printer = VecPrinter(Vec::new());
+    execute(code, &EagerEval, &mut printer).unwrap();
+    assert_eq!(printer.0, vec![Value::Int(3.into())]);
+}
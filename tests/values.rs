@@ -0,0 +1,97 @@
+mod common;
+
+use common::{run, run_values};
+use mini_lang::{ErrorKind, Value};
+
+fn kind_of(code: &str) -> ErrorKind {
+    run_values(code).unwrap_err().kind().cloned().unwrap()
+}
+
+#[test]
+fn literals() {
+    let code = indoc::indoc! {"
+        let truth = true
+        print truth
+        print false
+        print ()
+        print 42
+    "};
+    assert_eq!(
+        run_values(code).unwrap(),
+        vec![
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Unit,
+            Value::Int(42.into())
+        ]
+    );
+}
+
+#[test]
+fn comparison() {
+    let code = indoc::indoc! {"
+        print 2 > 1
+        print 1 >= 2
+        print true == (1 < 2)
+        print () == ()
+        print false != false
+    "};
+    let values = run_values(code).unwrap();
+    assert_eq!(
+        values,
+        [true, false, true, true, false]
+            .iter()
+            .map(|b| Value::Bool(*b))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn condition() {
+    let code = indoc::indoc! {"
+        def is_even(n) = if n == 0 then true else is_odd(n - 1)
+        def is_odd(n) = if n == 0 then false else is_even(n - 1)
+        print if is_even(4) then 1 else 0
+        print if is_odd(4) then 1 else 0
+    "};
+    assert_eq!(run(code).unwrap(), vec![1, 0]);
+}
+
+#[test]
+fn type_mismatch() {
+    assert_eq!(
+        kind_of("print (2 > 1) * 7\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a boolean")
+        }
+    );
+    assert_eq!(
+        kind_of("print true + 1\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a boolean")
+        }
+    );
+    assert_eq!(
+        kind_of("print if 1 then 2 else 3\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("a boolean"),
+            found: String::from("an integer")
+        }
+    );
+    assert_eq!(
+        kind_of("print 1 == true\n"),
+        ErrorKind::TypeMismatch {
+            expected: String::from("an integer"),
+            found: String::from("a boolean")
+        }
+    );
+}
+
+#[test]
+fn condition_span() {
+    let code = "print if 1 + 1 then 2 else 3\n";
+    let span = run(code).unwrap_err().span().unwrap();
+    assert_eq!(&code[span.start..span.end], "1 + 1");
+}